@@ -1,8 +1,5 @@
-use rs_firebase_admin_sdk::{
-    App,
-    auth::{FirebaseAuthService, UserList},
-    client::ApiHttpClient,
-};
+use futures::TryStreamExt;
+use rs_firebase_admin_sdk::{App, auth::FirebaseAuthService, client::ApiHttpClient};
 
 /// Generic method to print out all live users, fetch 10 at a time
 async fn print_all_users<A, C>(auth_admin: &A)
@@ -10,18 +7,14 @@ where
     A: FirebaseAuthService<C>,
     C: ApiHttpClient,
 {
-    let mut user_page: Option<UserList> = None;
-    loop {
-        user_page = auth_admin.list_users(10, user_page).await.unwrap();
-
-        if let Some(user_page) = &user_page {
-            for user in &user_page.users {
-                println!("User: {user:?}");
-            }
-        } else {
-            break;
-        }
-    }
+    auth_admin
+        .users_stream(10)
+        .try_for_each(|user| async move {
+            println!("User: {user:?}");
+            Ok(())
+        })
+        .await
+        .unwrap();
 }
 
 #[tokio::main]
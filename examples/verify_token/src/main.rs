@@ -3,8 +3,7 @@ use rs_firebase_admin_sdk::{App, jwt::TokenValidator};
 async fn verify_token<T: TokenValidator>(token: &str, validator: &T) {
     match validator.validate(token).await {
         Ok(token) => {
-            let user_id = token.get("sub").unwrap().as_str().unwrap();
-            println!("Token for user {user_id} is valid!")
+            println!("Token for user {} is valid!", token.uid)
         }
         Err(err) => {
             println!("Token is invalid because {err:?}!")
@@ -48,6 +48,60 @@ impl From<FirebaseAuthEmulatorRestApi> for &'static str {
     }
 }
 
+/// Identity Platform identity provider configuration REST API endpoints
+pub enum ProviderConfigRestApi {
+    OidcConfigs,
+    SamlConfigs,
+}
+
+impl From<ProviderConfigRestApi> for &'static str {
+    fn from(path: ProviderConfigRestApi) -> Self {
+        match path {
+            ProviderConfigRestApi::OidcConfigs => "/oauthIdpConfigs",
+            ProviderConfigRestApi::SamlConfigs => "/inboundSamlConfigs",
+        }
+    }
+}
+
+/// Identity Platform project configuration REST API endpoints
+pub enum ProjectConfigRestApi {
+    Config,
+}
+
+impl From<ProjectConfigRestApi> for &'static str {
+    fn from(path: ProjectConfigRestApi) -> Self {
+        match path {
+            ProjectConfigRestApi::Config => "/config",
+        }
+    }
+}
+
+/// Identity Platform tenant management REST API endpoints
+pub enum TenantManagementRestApi {
+    Tenants,
+}
+
+impl From<TenantManagementRestApi> for &'static str {
+    fn from(path: TenantManagementRestApi) -> Self {
+        match path {
+            TenantManagementRestApi::Tenants => "/tenants",
+        }
+    }
+}
+
+/// IAM Service Account Credentials REST API endpoints
+pub enum IamCredentialsRestApi {
+    SignBlob,
+}
+
+impl From<IamCredentialsRestApi> for &'static str {
+    fn from(path: IamCredentialsRestApi) -> Self {
+        match path {
+            IamCredentialsRestApi::SignBlob => ":signBlob",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiUriBuilder {
     root_prefix: String,
@@ -58,6 +112,11 @@ impl ApiUriBuilder {
         Self { root_prefix }
     }
 
+    /// Builder for resources nested under given path, e.g. `/tenants/{tenant_id}`
+    pub fn nested(&self, path: &str) -> Self {
+        Self::new(self.root_prefix.clone() + path)
+    }
+
     pub fn build<PathT: Into<&'static str>>(&self, path: PathT) -> String {
         self.root_prefix.clone() + path.into()
     }
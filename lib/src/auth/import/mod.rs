@@ -1,94 +1,391 @@
-use super::Claims;
-use serde::Serialize;
+//! Bulk user import
 
-#[derive(Serialize, Debug, Clone)]
+#[cfg(test)]
+mod test;
+
+use super::{BatchUserError, Claims, MultiFactorInfo};
+use crate::util::encoding::decode_base64;
+use crate::util::serialize::serialize_opt_str_epoch_ms;
+use crate::validation::{
+    Email, PhoneNumber, PhotoUrl, Uid, ValidationError, is_e164, is_email, is_uid, is_url,
+};
+use error_stack::Report;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use thiserror::Error;
+use time::OffsetDateTime;
+
+/// Maximum number of users imported by a single batch call
+pub const MAX_IMPORT_USERS_BATCH: usize = 1000;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum UserImportError {
+    #[error("User ID is missing")]
+    MissingUid,
+    #[error(transparent)]
+    InvalidAttribute(#[from] ValidationError),
+    #[error("Password hash is not valid base64")]
+    InvalidPasswordHash,
+    #[error("Password salt is not valid base64")]
+    InvalidSalt,
+    #[error("Password hash is set but import options carry no hash configuration")]
+    MissingHashOptions,
+    #[error("Hash key is not valid base64")]
+    InvalidHashKey,
+    #[error("Salt separator is not valid base64")]
+    InvalidSaltSeparator,
+    #[error("Hash rounds must be within {min}..={max}")]
+    InvalidRounds { min: u32, max: u32 },
+    #[error("Hash memory cost must be within {min}..={max}")]
+    InvalidMemoryCost { min: u32, max: u32 },
+    #[error("Standard scrypt parameters must be positive")]
+    InvalidScryptParameters,
+    #[error("Hash algorithm {0} is not supported")]
+    UnsupportedHashAlgorithm(String),
+    #[error("Hash configuration is missing {0}")]
+    MissingHashParameter(&'static str),
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum HashAlgorithmName {
-    #[serde(rename = "HMAC_SHA512")]
     HmacSha512,
-    #[serde(rename = "HMAC_SHA256")]
     HmacSha256,
-    #[serde(rename = "HMAC_SHA1")]
     HmacSha1,
-    #[serde(rename = "HMAC_MD5")]
     HmacMd5,
-    #[serde(rename = "SHA256")]
+    Md5,
+    Sha1,
     Sha256,
-    #[serde(rename = "SHA512")]
     Sha512,
-    #[serde(rename = "PBKDF_SHA1")]
-    Ppkdf2Sha1,
-    #[serde(rename = "PBKDF_SHA256")]
-    Ppkdf2Sha256,
-    #[serde(rename = "SCRYPT")]
+    PbkdfSha1,
+    #[serde(rename = "PBKDF2_SHA256")]
+    Pbkdf2Sha256,
     Scrypt,
-    #[serde(rename = "STANDARD_SCRYPT")]
     StandardScrypt,
-    #[serde(rename = "BCRYPT")]
     Bcrypt,
 }
 
-#[derive(Debug, Clone)]
+/// Algorithm and parameters the imported password hashes were created with,
+/// keys and salt separators are base64 encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PasswordHash {
     HmacSha512 {
-        hash: String,
-        salt: Option<String>,
         key: String,
     },
     HmacSha256 {
-        hash: String,
-        salt: Option<String>,
         key: String,
     },
     HmacSha1 {
-        hash: String,
-        salt: Option<String>,
         key: String,
     },
     HmacMd5 {
-        hash: String,
-        salt: Option<String>,
         key: String,
     },
+    Md5 {
+        rounds: u32,
+    },
+    Sha1 {
+        rounds: u32,
+    },
     Sha256 {
-        hash: String,
-        salt: Option<String>,
         rounds: u32,
     },
     Sha512 {
-        hash: String,
-        salt: Option<String>,
         rounds: u32,
     },
-    Ppkdf2Sha1 {
-        hash: String,
-        salt: Option<String>,
+    PbkdfSha1 {
         rounds: u32,
     },
-    Ppkdf2Sha256 {
-        hash: String,
-        salt: Option<String>,
+    Pbkdf2Sha256 {
         rounds: u32,
     },
+    /// Firebase modified scrypt
     Scrypt {
-        hash: String,
-        salt: Option<String>,
         key: String,
         rounds: u32,
-        memory_cost: u8,
+        memory_cost: u32,
         salt_separator: Option<String>,
     },
     StandardScrypt {
-        hash: String,
-        salt: Option<String>,
-        block_size: usize,
-        parallelization: usize,
-        memory_cost: u8,
-        dk_len: usize,
-    },
-    Bcrypt {
-        hash: String,
-        salt: Option<String>,
+        block_size: u32,
+        parallelization: u32,
+        memory_cost: u32,
+        dk_len: u32,
     },
+    Bcrypt,
+}
+
+impl PasswordHash {
+    pub fn algorithm(&self) -> HashAlgorithmName {
+        match self {
+            Self::HmacSha512 { .. } => HashAlgorithmName::HmacSha512,
+            Self::HmacSha256 { .. } => HashAlgorithmName::HmacSha256,
+            Self::HmacSha1 { .. } => HashAlgorithmName::HmacSha1,
+            Self::HmacMd5 { .. } => HashAlgorithmName::HmacMd5,
+            Self::Md5 { .. } => HashAlgorithmName::Md5,
+            Self::Sha1 { .. } => HashAlgorithmName::Sha1,
+            Self::Sha256 { .. } => HashAlgorithmName::Sha256,
+            Self::Sha512 { .. } => HashAlgorithmName::Sha512,
+            Self::PbkdfSha1 { .. } => HashAlgorithmName::PbkdfSha1,
+            Self::Pbkdf2Sha256 { .. } => HashAlgorithmName::Pbkdf2Sha256,
+            Self::Scrypt { .. } => HashAlgorithmName::Scrypt,
+            Self::StandardScrypt { .. } => HashAlgorithmName::StandardScrypt,
+            Self::Bcrypt => HashAlgorithmName::Bcrypt,
+        }
+    }
+
+    /// Check parameters are within the ranges accepted by the API
+    pub fn validate(&self) -> Result<(), UserImportError> {
+        match self {
+            Self::HmacSha512 { key }
+            | Self::HmacSha256 { key }
+            | Self::HmacSha1 { key }
+            | Self::HmacMd5 { key } => check_key(key),
+            Self::Md5 { rounds } => check_rounds(*rounds, 0..=8192),
+            Self::Sha1 { rounds } | Self::Sha256 { rounds } | Self::Sha512 { rounds } => {
+                check_rounds(*rounds, 1..=8192)
+            }
+            Self::PbkdfSha1 { rounds } | Self::Pbkdf2Sha256 { rounds } => {
+                check_rounds(*rounds, 0..=120000)
+            }
+            Self::Scrypt {
+                key,
+                rounds,
+                memory_cost,
+                salt_separator,
+            } => {
+                check_key(key)?;
+                check_rounds(*rounds, 1..=8)?;
+                if !(1..=14).contains(memory_cost) {
+                    return Err(UserImportError::InvalidMemoryCost { min: 1, max: 14 });
+                }
+                if salt_separator.as_deref().is_some_and(|sep| !is_base64(sep)) {
+                    return Err(UserImportError::InvalidSaltSeparator);
+                }
+
+                Ok(())
+            }
+            Self::StandardScrypt {
+                block_size,
+                parallelization,
+                memory_cost,
+                dk_len,
+            } => {
+                if [block_size, parallelization, memory_cost, dk_len].contains(&&0) {
+                    return Err(UserImportError::InvalidScryptParameters);
+                }
+
+                Ok(())
+            }
+            Self::Bcrypt => Ok(()),
+        }
+    }
+}
+
+/// Hash configuration as returned in project config `signIn.hashConfig`, or as shown by the
+/// Firebase console for use with `firebase auth:import`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HashConfig {
+    pub algorithm: String,
+    #[serde(alias = "base64_signer_key")]
+    pub signer_key: Option<String>,
+    #[serde(alias = "base64_salt_separator")]
+    pub salt_separator: Option<String>,
+    pub rounds: Option<u32>,
+    #[serde(alias = "mem_cost")]
+    pub memory_cost: Option<u32>,
+    pub parallelization: Option<u32>,
+    pub block_size: Option<u32>,
+    #[serde(alias = "dk_len")]
+    pub dk_len: Option<u32>,
+}
+
+impl TryFrom<&HashConfig> for PasswordHash {
+    type Error = Report<UserImportError>;
+
+    fn try_from(config: &HashConfig) -> Result<Self, Self::Error> {
+        let key = || {
+            config
+                .signer_key
+                .clone()
+                .ok_or(UserImportError::MissingHashParameter("signer key"))
+        };
+        let rounds = || {
+            config
+                .rounds
+                .ok_or(UserImportError::MissingHashParameter("rounds"))
+        };
+        let memory_cost = || {
+            config
+                .memory_cost
+                .ok_or(UserImportError::MissingHashParameter("memory cost"))
+        };
+
+        let hash = match config.algorithm.to_uppercase().replace('-', "_").as_str() {
+            "HMAC_SHA512" => Self::HmacSha512 { key: key()? },
+            "HMAC_SHA256" => Self::HmacSha256 { key: key()? },
+            "HMAC_SHA1" => Self::HmacSha1 { key: key()? },
+            "HMAC_MD5" => Self::HmacMd5 { key: key()? },
+            "MD5" => Self::Md5 { rounds: rounds()? },
+            "SHA1" => Self::Sha1 { rounds: rounds()? },
+            "SHA256" => Self::Sha256 { rounds: rounds()? },
+            "SHA512" => Self::Sha512 { rounds: rounds()? },
+            "PBKDF_SHA1" => Self::PbkdfSha1 { rounds: rounds()? },
+            "PBKDF2_SHA256" => Self::Pbkdf2Sha256 { rounds: rounds()? },
+            "SCRYPT" => Self::Scrypt {
+                key: key()?,
+                rounds: rounds()?,
+                memory_cost: memory_cost()?,
+                salt_separator: config.salt_separator.clone(),
+            },
+            "STANDARD_SCRYPT" => Self::StandardScrypt {
+                block_size: config
+                    .block_size
+                    .ok_or(UserImportError::MissingHashParameter("block size"))?,
+                parallelization: config
+                    .parallelization
+                    .ok_or(UserImportError::MissingHashParameter("parallelization"))?,
+                memory_cost: memory_cost()?,
+                dk_len: config
+                    .dk_len
+                    .ok_or(UserImportError::MissingHashParameter("derived key length"))?,
+            },
+            "BCRYPT" => Self::Bcrypt,
+            _ => {
+                return Err(Report::new(UserImportError::UnsupportedHashAlgorithm(
+                    config.algorithm.clone(),
+                )));
+            }
+        };
+
+        Ok(hash)
+    }
+}
+
+fn is_base64(value: &str) -> bool {
+    decode_base64(value).is_ok()
+}
+
+fn check_key(key: &str) -> Result<(), UserImportError> {
+    if key.is_empty() || !is_base64(key) {
+        return Err(UserImportError::InvalidHashKey);
+    }
+
+    Ok(())
+}
+
+fn check_rounds(rounds: u32, range: RangeInclusive<u32>) -> Result<(), UserImportError> {
+    if !range.contains(&rounds) {
+        return Err(UserImportError::InvalidRounds {
+            min: *range.start(),
+            max: *range.end(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Options applying to all records of an import
+#[derive(Debug, Clone, Default)]
+pub struct UserImportOptions {
+    /// Required when any record carries a password hash
+    pub hash: Option<PasswordHash>,
+}
+
+impl UserImportOptions {
+    pub fn with_hash(hash: PasswordHash) -> Self {
+        Self { hash: Some(hash) }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct HashOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash_algorithm: Option<HashAlgorithmName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signer_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rounds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory_cost: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    salt_separator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parallelization: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dk_len: Option<u32>,
+}
+
+impl From<&PasswordHash> for HashOptions {
+    fn from(hash: &PasswordHash) -> Self {
+        let options = Self {
+            hash_algorithm: Some(hash.algorithm()),
+            ..Default::default()
+        };
+
+        match hash.clone() {
+            PasswordHash::HmacSha512 { key }
+            | PasswordHash::HmacSha256 { key }
+            | PasswordHash::HmacSha1 { key }
+            | PasswordHash::HmacMd5 { key } => Self {
+                signer_key: Some(key),
+                ..options
+            },
+            PasswordHash::Md5 { rounds }
+            | PasswordHash::Sha1 { rounds }
+            | PasswordHash::Sha256 { rounds }
+            | PasswordHash::Sha512 { rounds }
+            | PasswordHash::PbkdfSha1 { rounds }
+            | PasswordHash::Pbkdf2Sha256 { rounds } => Self {
+                rounds: Some(rounds),
+                ..options
+            },
+            PasswordHash::Scrypt {
+                key,
+                rounds,
+                memory_cost,
+                salt_separator,
+            } => Self {
+                signer_key: Some(key),
+                rounds: Some(rounds),
+                memory_cost: Some(memory_cost),
+                salt_separator,
+                ..options
+            },
+            PasswordHash::StandardScrypt {
+                block_size,
+                parallelization,
+                memory_cost,
+                dk_len,
+            } => Self {
+                block_size: Some(block_size),
+                parallelization: Some(parallelization),
+                memory_cost: Some(memory_cost),
+                dk_len: Some(dk_len),
+                ..options
+            },
+            PasswordHash::Bcrypt => options,
+        }
+    }
+}
+
+/// Identity provider linked to an imported user
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserProviderRecord {
+    pub provider_id: String,
+    /// User ID at the provider
+    pub raw_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
 }
 
 #[derive(Serialize, Debug, Clone, Default)]
@@ -107,37 +404,68 @@ pub struct UserImportRecord {
     pub phone_number: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email_verified: Option<bool>,
+    /// Base64 encoded salt
     #[serde(skip_serializing_if = "Option::is_none")]
     pub salt: Option<String>,
+    /// Base64 encoded password hash
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub hash_algorithm: Option<HashAlgorithmName>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub signer_key: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub memory_cost: Option<u8>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub parallelization: Option<usize>,
+    #[serde(rename = "customAttributes")]
+    pub custom_claims: Option<Claims>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub block_size: Option<usize>,
+    pub disabled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub dk_len: Option<usize>,
+    pub mfa_info: Option<Vec<MultiFactorInfo>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub rounds: Option<u32>,
+    pub provider_user_info: Option<Vec<UserProviderRecord>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub salt_separator: Option<String>,
+    #[serde(serialize_with = "serialize_opt_str_epoch_ms")]
+    pub created_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "customAttributes")]
-    pub custom_claims: Option<Claims>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub disabled: Option<bool>,
+    #[serde(serialize_with = "serialize_opt_str_epoch_ms")]
+    pub last_login_at: Option<OffsetDateTime>,
 }
 
 impl UserImportRecord {
     pub fn builder() -> UserImportRecordBuilder {
         UserImportRecordBuilder::default()
     }
+
+    /// Check the record can be imported with given options
+    pub fn validate(&self, options: &UserImportOptions) -> Result<(), UserImportError> {
+        match &self.uid {
+            None => return Err(UserImportError::MissingUid),
+            Some(uid) if !is_uid(uid) => return Err(ValidationError::InvalidUid.into()),
+            Some(_) => {}
+        }
+        if self.email.as_deref().is_some_and(|email| !is_email(email)) {
+            return Err(ValidationError::InvalidEmail.into());
+        }
+        if self
+            .phone_number
+            .as_deref()
+            .is_some_and(|phone_number| !is_e164(phone_number))
+        {
+            return Err(ValidationError::InvalidPhoneNumber.into());
+        }
+        if self.photo_url.as_deref().is_some_and(|url| !is_url(url)) {
+            return Err(ValidationError::InvalidPhotoUrl.into());
+        }
+        if let Some(password_hash) = &self.password_hash {
+            if !is_base64(password_hash) {
+                return Err(UserImportError::InvalidPasswordHash);
+            }
+            if options.hash.is_none() {
+                return Err(UserImportError::MissingHashOptions);
+            }
+        }
+        if self.salt.as_deref().is_some_and(|salt| !is_base64(salt)) {
+            return Err(UserImportError::InvalidSalt);
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Default)]
@@ -150,14 +478,14 @@ impl UserImportRecordBuilder {
         self.record
     }
 
-    pub fn with_uid(mut self, uid: String) -> Self {
-        self.record.uid = Some(uid);
+    pub fn with_uid(mut self, uid: Uid) -> Self {
+        self.record.uid = Some(uid.into());
 
         self
     }
 
-    pub fn with_email(mut self, email: String, verified: bool) -> Self {
-        self.record.email = Some(email);
+    pub fn with_email(mut self, email: Email, verified: bool) -> Self {
+        self.record.email = Some(email.into());
         self.record.email_verified = Some(verified);
 
         self
@@ -169,14 +497,14 @@ impl UserImportRecordBuilder {
         self
     }
 
-    pub fn with_photo_url(mut self, photo_url: String) -> Self {
-        self.record.photo_url = Some(photo_url);
+    pub fn with_photo_url(mut self, photo_url: PhotoUrl) -> Self {
+        self.record.photo_url = Some(photo_url.into());
 
         self
     }
 
-    pub fn with_phone_number(mut self, phone_number: String) -> Self {
-        self.record.phone_number = Some(phone_number);
+    pub fn with_phone_number(mut self, phone_number: PhoneNumber) -> Self {
+        self.record.phone_number = Some(phone_number.into());
 
         self
     }
@@ -193,94 +521,38 @@ impl UserImportRecordBuilder {
         self
     }
 
-    pub fn with_password(mut self, password: PasswordHash) -> Self {
-        match password {
-            PasswordHash::HmacSha512 { hash, salt, key } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::HmacSha512);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.signer_key = Some(key);
-            }
-            PasswordHash::HmacSha256 { hash, salt, key } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::HmacSha256);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.signer_key = Some(key);
-            }
-            PasswordHash::HmacSha1 { hash, salt, key } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::HmacSha1);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.signer_key = Some(key);
-            }
-            PasswordHash::HmacMd5 { hash, salt, key } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::HmacMd5);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.signer_key = Some(key);
-            }
-            PasswordHash::Sha256 { hash, salt, rounds } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::Sha256);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.rounds = Some(rounds);
-            }
-            PasswordHash::Sha512 { hash, salt, rounds } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::Sha512);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.rounds = Some(rounds);
-            }
-            PasswordHash::Ppkdf2Sha1 { hash, salt, rounds } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::Ppkdf2Sha1);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.rounds = Some(rounds);
-            }
-            PasswordHash::Ppkdf2Sha256 { hash, salt, rounds } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::Ppkdf2Sha256);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.rounds = Some(rounds);
-            }
-            PasswordHash::Scrypt {
-                hash,
-                salt,
-                rounds,
-                key,
-                memory_cost,
-                salt_separator,
-            } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::Scrypt);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.rounds = Some(rounds);
-                self.record.signer_key = Some(key);
-                self.record.memory_cost = Some(memory_cost);
-                self.record.salt_separator = salt_separator;
-            }
-            PasswordHash::StandardScrypt {
-                hash,
-                salt,
-                memory_cost,
-                block_size,
-                parallelization,
-                dk_len,
-            } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::StandardScrypt);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-                self.record.memory_cost = Some(memory_cost);
-                self.record.block_size = Some(block_size);
-                self.record.parallelization = Some(parallelization);
-                self.record.dk_len = Some(dk_len);
-            }
-            PasswordHash::Bcrypt { hash, salt } => {
-                self.record.hash_algorithm = Some(HashAlgorithmName::Bcrypt);
-                self.record.password_hash = Some(hash);
-                self.record.salt = salt;
-            }
-        }
+    /// Enrolled second factors, an empty list imports the user without any
+    pub fn with_multi_factor(mut self, factors: Vec<MultiFactorInfo>) -> Self {
+        self.record.mfa_info = Some(factors);
+
+        self
+    }
+
+    pub fn with_provider(mut self, provider: UserProviderRecord) -> Self {
+        self.record
+            .provider_user_info
+            .get_or_insert(Vec::new())
+            .push(provider);
+
+        self
+    }
+
+    pub fn with_created_at(mut self, created_at: OffsetDateTime) -> Self {
+        self.record.created_at = Some(created_at);
+
+        self
+    }
+
+    pub fn with_last_login_at(mut self, last_login_at: OffsetDateTime) -> Self {
+        self.record.last_login_at = Some(last_login_at);
+
+        self
+    }
+
+    /// Base64 encoded password hash and salt, created with the algorithm set in `UserImportOptions`
+    pub fn with_password_hash(mut self, hash: String, salt: Option<String>) -> Self {
+        self.record.password_hash = Some(hash);
+        self.record.salt = salt;
 
         self
     }
@@ -290,4 +562,33 @@ impl UserImportRecordBuilder {
 #[serde(rename_all = "camelCase")]
 pub struct UserImportRecords {
     pub users: Vec<UserImportRecord>,
+    #[serde(flatten)]
+    hash_options: HashOptions,
+}
+
+impl UserImportRecords {
+    pub fn new(users: Vec<UserImportRecord>, options: &UserImportOptions) -> Self {
+        Self {
+            users,
+            hash_options: options
+                .hash
+                .as_ref()
+                .map(HashOptions::from)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct UserImportResponse {
+    #[serde(default, rename = "error")]
+    pub errors: Vec<BatchUserError>,
+}
+
+/// Outcome of importing users in bulk
+#[derive(Debug, Clone, Default)]
+pub struct UserImportResult {
+    pub success_count: usize,
+    pub failure_count: usize,
+    pub errors: Vec<BatchUserError>,
 }
@@ -0,0 +1,212 @@
+use super::{
+    PasswordHash, UserImportError, UserImportOptions, UserImportRecord, UserImportRecords,
+};
+use crate::App;
+use crate::auth::FirebaseAuthService;
+use crate::client::error::ApiClientError;
+use crate::util::test_server::{StubResponse, TestServer};
+use crate::validation::{Email, Uid, ValidationError};
+use serde_json::{Value, from_slice, json, to_value};
+
+fn record(uid: &str) -> UserImportRecord {
+    UserImportRecord::builder()
+        .with_uid(Uid::new(uid).unwrap())
+        .with_email(Email::new(format!("{uid}@example.com")).unwrap(), true)
+        .with_password_hash("aGFzaA==".into(), Some("c2FsdA".into()))
+        .build()
+}
+
+#[test]
+fn test_hash_options_sent_once_per_batch() {
+    let options = UserImportOptions::with_hash(PasswordHash::Scrypt {
+        key: "a2V5".into(),
+        rounds: 8,
+        memory_cost: 14,
+        salt_separator: Some("Bw==".into()),
+    });
+
+    let body = to_value(UserImportRecords::new(vec![record("user1")], &options)).unwrap();
+
+    assert_eq!(
+        body,
+        json!({
+            "users": [{
+                "localId": "user1",
+                "email": "user1@example.com",
+                "emailVerified": true,
+                "passwordHash": "aGFzaA==",
+                "salt": "c2FsdA"
+            }],
+            "hashAlgorithm": "SCRYPT",
+            "signerKey": "a2V5",
+            "rounds": 8,
+            "memoryCost": 14,
+            "saltSeparator": "Bw=="
+        })
+    );
+}
+
+#[test]
+fn test_hash_options_validation() {
+    let invalid = [
+        (
+            PasswordHash::HmacSha256 { key: "".into() },
+            UserImportError::InvalidHashKey,
+        ),
+        (
+            PasswordHash::Sha512 { rounds: 0 },
+            UserImportError::InvalidRounds { min: 1, max: 8192 },
+        ),
+        (
+            PasswordHash::Pbkdf2Sha256 { rounds: 120001 },
+            UserImportError::InvalidRounds {
+                min: 0,
+                max: 120000,
+            },
+        ),
+        (
+            PasswordHash::Scrypt {
+                key: "a2V5".into(),
+                rounds: 8,
+                memory_cost: 15,
+                salt_separator: None,
+            },
+            UserImportError::InvalidMemoryCost { min: 1, max: 14 },
+        ),
+        (
+            PasswordHash::StandardScrypt {
+                block_size: 8,
+                parallelization: 0,
+                memory_cost: 1024,
+                dk_len: 64,
+            },
+            UserImportError::InvalidScryptParameters,
+        ),
+    ];
+
+    for (hash, expected) in invalid {
+        assert_eq!(hash.validate(), Err(expected));
+    }
+
+    assert!(PasswordHash::Md5 { rounds: 0 }.validate().is_ok());
+    assert!(PasswordHash::Bcrypt.validate().is_ok());
+}
+
+#[test]
+fn test_record_validation() {
+    let options = UserImportOptions::with_hash(PasswordHash::Bcrypt);
+    let cases = [
+        (UserImportRecord::default(), UserImportError::MissingUid),
+        (
+            UserImportRecord {
+                uid: Some("a".repeat(129)),
+                ..Default::default()
+            },
+            UserImportError::InvalidAttribute(ValidationError::InvalidUid),
+        ),
+        (
+            UserImportRecord {
+                email: Some("not-an-email".into()),
+                ..record("user1")
+            },
+            UserImportError::InvalidAttribute(ValidationError::InvalidEmail),
+        ),
+        (
+            UserImportRecord {
+                phone_number: Some("555-1234".into()),
+                ..record("user1")
+            },
+            UserImportError::InvalidAttribute(ValidationError::InvalidPhoneNumber),
+        ),
+        (
+            UserImportRecord {
+                photo_url: Some("me.jpg".into()),
+                ..record("user1")
+            },
+            UserImportError::InvalidAttribute(ValidationError::InvalidPhotoUrl),
+        ),
+        (
+            UserImportRecord::builder()
+                .with_uid(Uid::new("user1").unwrap())
+                .with_password_hash("not base64!".into(), None)
+                .build(),
+            UserImportError::InvalidPasswordHash,
+        ),
+        (
+            UserImportRecord::builder()
+                .with_uid(Uid::new("user1").unwrap())
+                .with_password_hash("aGFzaA".into(), Some("%%".into()))
+                .build(),
+            UserImportError::InvalidSalt,
+        ),
+    ];
+
+    for (record, expected) in cases {
+        assert_eq!(record.validate(&options), Err(expected));
+    }
+
+    assert_eq!(
+        record("user1").validate(&UserImportOptions::default()),
+        Err(UserImportError::MissingHashOptions)
+    );
+    assert_eq!(record("user1").validate(&options), Ok(()));
+}
+
+#[tokio::test]
+async fn test_import_skips_invalid_records() {
+    let server = TestServer::start(|_| {
+        StubResponse::json(json!({"error": [{"index": 1, "message": "Duplicate"}]}).to_string())
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+    let options = UserImportOptions::with_hash(PasswordHash::HmacSha256 { key: "a2V5".into() });
+
+    let result = auth
+        .import_users(
+            vec![
+                record("user0"),
+                UserImportRecord {
+                    email: Some("invalid".into()),
+                    ..record("user1")
+                },
+                record("user2"),
+            ],
+            options,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.success_count, 1);
+    assert_eq!(result.failure_count, 2);
+    assert_eq!(result.errors[0].index, 1);
+    assert_eq!(
+        result.errors[0].reason,
+        Some(UserImportError::InvalidAttribute(
+            ValidationError::InvalidEmail
+        ))
+    );
+    // Server indexes refer to the sent batch, which skipped the invalid record
+    assert_eq!(result.errors[1].index, 2);
+    assert_eq!(result.errors[1].reason, None);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let body: Value = from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["users"].as_array().unwrap().len(), 2);
+    assert_eq!(body["hashAlgorithm"], "HMAC_SHA256");
+
+    let invalid_options = UserImportOptions::with_hash(PasswordHash::Sha256 { rounds: 0 });
+    let report = auth
+        .import_users(vec![record("user3")], invalid_options)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        ApiClientError::InvalidImportOptions
+    ));
+    assert_eq!(
+        report.downcast_ref::<UserImportError>(),
+        Some(&UserImportError::InvalidRounds { min: 1, max: 8192 })
+    );
+    assert_eq!(server.requests().len(), 1);
+}
@@ -6,23 +6,47 @@ mod test;
 pub mod claims;
 pub mod import;
 pub mod oob_code;
+pub mod provider_config;
+pub mod token;
 
 use crate::api_uri::{ApiUriBuilder, FirebaseAuthEmulatorRestApi, FirebaseAuthRestApi};
 use crate::client::ApiHttpClient;
-use crate::client::error::ApiClientError;
-use crate::util::{I128EpochMs, StrEpochMs, StrEpochSec};
+use crate::client::error::{ApiClientError, AuthErrorCode, split_error_message};
+use crate::signer::{CredentialsSigner, EmulatorSigner, Signer};
+use crate::util::prefetch::Prefetch;
+use crate::util::serialize::{
+    deserialize_opt_i128_epoch_ms, deserialize_opt_str_epoch_ms, deserialize_opt_str_epoch_sec,
+    serialize_opt_str_epoch_sec,
+};
+use crate::validation::{
+    Email, Password, PhoneNumber, PhotoUrl, TenantId, Uid, ValidationError, is_e164, is_email,
+    is_password, is_uid, is_url,
+};
 pub use claims::Claims;
-use error_stack::Report;
+use error_stack::{Report, ResultExt};
+use futures::{Stream, StreamExt, TryStreamExt, stream};
 use http::Method;
-pub use import::{UserImportRecord, UserImportRecords};
+use import::{MAX_IMPORT_USERS_BATCH, UserImportResponse};
+pub use import::{
+    UserImportError, UserImportOptions, UserImportRecord, UserImportRecords, UserImportResult,
+};
 use oob_code::{OobCodeAction, OobCodeActionLink, OobCodeActionType};
+use provider_config::ProviderConfigService;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::future::Future;
 use std::vec;
 use time::{Duration, OffsetDateTime};
+use token::{CustomTokenError, encode_custom_token};
 
-const FIREBASE_AUTH_REST_AUTHORITY: &str = "identitytoolkit.googleapis.com";
+pub(crate) const FIREBASE_AUTH_REST_AUTHORITY: &str = "identitytoolkit.googleapis.com";
+
+/// Maximum number of identifiers accepted by a single user lookup
+pub const MAX_GET_USERS_IDENTIFIERS: usize = 100;
+/// Maximum number of users deleted by a single batch call
+pub const MAX_DELETE_USERS_BATCH: usize = 1000;
+/// Number of lookup batches `get_users_bulk` keeps in flight
+const GET_USERS_BULK_CONCURRENCY: usize = 4;
 
 #[derive(Serialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
@@ -33,49 +57,240 @@ pub struct NewUser {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<bool>,
+    /// Second factors enrolled on creation, enrollment IDs are assigned by the server
+    #[serde(rename = "mfaInfo")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_factor: Option<Vec<MultiFactorInfo>>,
+    /// Identity Platform tenant to create the user in, defaults to the tenant of the client
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
 }
 
 impl NewUser {
+    pub fn builder() -> NewUserBuilder {
+        NewUserBuilder::default()
+    }
+
     pub fn email_and_password(email: String, password: String) -> Self {
         Self {
-            uid: None,
             email: Some(email),
             password: Some(password),
+            ..Default::default()
         }
     }
+
+    /// Check attributes the way the server would, before sending them
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.uid.as_deref().is_some_and(|uid| !is_uid(uid)) {
+            return Err(ValidationError::InvalidUid);
+        }
+        if self.email.as_deref().is_some_and(|email| !is_email(email)) {
+            return Err(ValidationError::InvalidEmail);
+        }
+        if self
+            .password
+            .as_deref()
+            .is_some_and(|password| !is_password(password))
+        {
+            return Err(ValidationError::InvalidPassword);
+        }
+        if self
+            .display_name
+            .as_deref()
+            .is_some_and(|name| name.is_empty())
+        {
+            return Err(ValidationError::InvalidDisplayName);
+        }
+        if self.photo_url.as_deref().is_some_and(|url| !is_url(url)) {
+            return Err(ValidationError::InvalidPhotoUrl);
+        }
+        let phone_numbers = self.phone_number.iter().chain(
+            self.multi_factor
+                .iter()
+                .flatten()
+                .filter_map(|factor| factor.phone_info.as_ref()),
+        );
+        for phone_number in phone_numbers {
+            if !is_e164(phone_number) {
+                return Err(ValidationError::InvalidPhoneNumber);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct NewUserBuilder {
+    user: NewUser,
+}
+
+impl NewUserBuilder {
+    /// User ID, generated by the server when not set
+    pub fn uid(mut self, value: Uid) -> Self {
+        self.user.uid = Some(value.into());
+
+        self
+    }
+
+    pub fn email(mut self, value: Email) -> Self {
+        self.user.email = Some(value.into());
+
+        self
+    }
+
+    pub fn email_verified(mut self, value: bool) -> Self {
+        self.user.email_verified = Some(value);
+
+        self
+    }
+
+    pub fn password(mut self, value: Password) -> Self {
+        self.user.password = Some(value.into_inner());
+
+        self
+    }
+
+    pub fn display_name(mut self, value: String) -> Self {
+        self.user.display_name = Some(value);
+
+        self
+    }
+
+    pub fn photo_url(mut self, value: PhotoUrl) -> Self {
+        self.user.photo_url = Some(value.into());
+
+        self
+    }
+
+    pub fn phone_number(mut self, value: PhoneNumber) -> Self {
+        self.user.phone_number = Some(value.into());
+
+        self
+    }
+
+    pub fn disabled(mut self, is_disabled: bool) -> Self {
+        self.user.disabled = Some(is_disabled);
+
+        self
+    }
+
+    pub fn multi_factor(mut self, enrollments: Vec<MultiFactorInfo>) -> Self {
+        self.user.multi_factor = Some(enrollments);
+
+        self
+    }
+
+    pub fn tenant_id(mut self, value: String) -> Self {
+        self.user.tenant_id = Some(value);
+
+        self
+    }
+
+    pub fn build(self) -> NewUser {
+        self.user
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ProviderUserInfo {
     pub provider_id: String,
+    pub display_name: Option<String>,
+    pub photo_url: Option<String>,
     pub email: Option<String>,
     pub phone_number: Option<String>,
     pub federated_id: Option<String>,
     pub raw_id: String,
 }
 
+/// TOTP second factor details, currently carries no data
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TotpInfo {}
+
+/// Second factor enrolled by a user
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiFactorInfo {
+    #[serde(rename = "mfaEnrollmentId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrollment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Phone number of SMS factor in E.164 format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_info: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub totp_info: Option<TotpInfo>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrolled_at: Option<OffsetDateTime>,
+}
+
+impl MultiFactorInfo {
+    /// SMS second factor for given phone number
+    pub fn phone(phone_number: PhoneNumber, display_name: Option<String>) -> Self {
+        Self {
+            display_name,
+            phone_info: Some(phone_number.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Account lifecycle timestamps of a user
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UserMetadata {
+    #[serde(default, deserialize_with = "deserialize_opt_str_epoch_ms")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "deserialize_opt_str_epoch_ms")]
+    pub last_login_at: Option<OffsetDateTime>,
+    /// Last time an ID token was minted for the user
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_refresh_at: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "deserialize_opt_i128_epoch_ms")]
+    pub password_updated_at: Option<OffsetDateTime>,
+    /// Sessions started before this time are revoked
+    #[serde(default, deserialize_with = "deserialize_opt_str_epoch_sec")]
+    pub valid_since: Option<OffsetDateTime>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     #[serde(rename = "localId")]
     pub uid: String,
+    pub tenant_id: Option<String>,
     pub email: Option<String>,
+    /// Email the account was created with, before any change
+    pub initial_email: Option<String>,
     pub display_name: Option<String>,
     pub photo_url: Option<String>,
     pub phone_number: Option<String>,
-    pub last_login_at: Option<StrEpochMs>,
     pub email_verified: Option<bool>,
-    pub password_updated_at: Option<I128EpochMs>,
-    pub valid_since: Option<StrEpochSec>,
-    pub created_at: Option<StrEpochMs>,
+    #[serde(flatten)]
+    pub metadata: UserMetadata,
+    /// Whether the user was created by signing in with a custom token
+    pub custom_auth: Option<bool>,
     pub salt: Option<String>,
     pub password_hash: Option<String>,
     pub provider_user_info: Option<Vec<ProviderUserInfo>>,
     #[serde(rename = "customAttributes")]
     pub custom_claims: Option<Claims>,
     pub disabled: Option<bool>,
+    pub mfa_info: Option<Vec<MultiFactorInfo>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -123,7 +338,7 @@ pub struct UserIdentifiers {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub phone_number: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub federated_user_id: Option<FederatedUserId>,
+    pub federated_user_id: Option<Vec<FederatedUserId>>,
 }
 
 impl UserIdentifiers {
@@ -138,7 +353,8 @@ pub struct UserIdentifiersBuilder {
 }
 
 impl UserIdentifiersBuilder {
-    pub fn with_email(mut self, email: String) -> Self {
+    pub fn with_email(mut self, email: Email) -> Self {
+        let email = email.into();
         match &mut self.ids.email {
             Some(email_vec) => email_vec.push(email),
             None => self.ids.email = Some(vec![email]),
@@ -147,7 +363,8 @@ impl UserIdentifiersBuilder {
         self
     }
 
-    pub fn with_uid(mut self, uid: String) -> Self {
+    pub fn with_uid(mut self, uid: Uid) -> Self {
+        let uid = uid.into();
         match &mut self.ids.uid {
             Some(uid_vec) => uid_vec.push(uid),
             None => self.ids.uid = Some(vec![uid]),
@@ -156,7 +373,8 @@ impl UserIdentifiersBuilder {
         self
     }
 
-    pub fn with_phone_number(mut self, pnumber: String) -> Self {
+    pub fn with_phone_number(mut self, pnumber: PhoneNumber) -> Self {
+        let pnumber = pnumber.into();
         match &mut self.ids.phone_number {
             Some(pnumber_vec) => pnumber_vec.push(pnumber),
             None => self.ids.phone_number = Some(vec![pnumber]),
@@ -165,11 +383,79 @@ impl UserIdentifiersBuilder {
         self
     }
 
+    pub fn with_federated_user_id(mut self, provider_id: String, raw_id: String) -> Self {
+        self.ids
+            .federated_user_id
+            .get_or_insert(Vec::new())
+            .push(FederatedUserId {
+                provider_id,
+                raw_id,
+            });
+
+        self
+    }
+
+    pub fn with_identifier(self, identifier: UserIdentifier) -> Self {
+        match identifier {
+            UserIdentifier::Uid(uid) => self.with_uid(uid),
+            UserIdentifier::Email(email) => self.with_email(email),
+            UserIdentifier::PhoneNumber(pnumber) => self.with_phone_number(pnumber),
+            UserIdentifier::Provider {
+                provider_id,
+                raw_id,
+            } => self.with_federated_user_id(provider_id, raw_id),
+        }
+    }
+
     pub fn build(self) -> UserIdentifiers {
         self.ids
     }
 }
 
+/// Single user identifier used in bulk lookups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserIdentifier {
+    Uid(Uid),
+    Email(Email),
+    PhoneNumber(PhoneNumber),
+    /// User ID assigned by a federated identity provider
+    Provider {
+        provider_id: String,
+        raw_id: String,
+    },
+}
+
+impl UserIdentifier {
+    /// Whether the user is the one identified
+    pub fn matches(&self, user: &User) -> bool {
+        match self {
+            Self::Uid(uid) => user.uid == uid.as_str(),
+            Self::Email(email) => user
+                .email
+                .as_ref()
+                .is_some_and(|user_email| user_email.eq_ignore_ascii_case(email.as_str())),
+            Self::PhoneNumber(pnumber) => user.phone_number.as_deref() == Some(pnumber.as_str()),
+            Self::Provider {
+                provider_id,
+                raw_id,
+            } => user
+                .provider_user_info
+                .iter()
+                .flatten()
+                .any(|info| &info.provider_id == provider_id && &info.raw_id == raw_id),
+        }
+    }
+}
+
+/// Outcome of a bulk user lookup
+#[derive(Debug, Clone, Default)]
+pub struct GetUsersResult {
+    /// Users matching at least one identifier, each user only once
+    pub found: Vec<User>,
+    /// Identifiers that did not match any user
+    pub not_found: Vec<UserIdentifier>,
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub enum DeleteAttribute {
     #[serde(rename = "DISPLAY_NAME")]
@@ -184,6 +470,14 @@ pub enum DeleteProvider {
     Phone,
 }
 
+/// Enrolled second factors, all factors are removed when `enrollments` is not set
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiFactorEnrollments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrollments: Option<Vec<MultiFactorInfo>>,
+}
+
 #[derive(Serialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct UserUpdate {
@@ -194,6 +488,7 @@ pub struct UserUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "serialize_opt_str_epoch_sec")]
     pub valid_since: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email_verified: Option<bool>,
@@ -212,12 +507,55 @@ pub struct UserUpdate {
     pub delete_attribute: Option<Vec<DeleteAttribute>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delete_provider: Option<Vec<DeleteProvider>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa: Option<MultiFactorEnrollments>,
 }
 
 impl UserUpdate {
-    pub fn builder(uid: String) -> UserUpdateBuilder {
+    pub fn builder(uid: Uid) -> UserUpdateBuilder {
         UserUpdateBuilder::new(uid)
     }
+
+    /// Check changed attributes the way the server would, before sending them
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !is_uid(&self.uid) {
+            return Err(ValidationError::InvalidUid);
+        }
+        if self.email.as_deref().is_some_and(|email| !is_email(email)) {
+            return Err(ValidationError::InvalidEmail);
+        }
+        if self
+            .password
+            .as_deref()
+            .is_some_and(|password| !is_password(password))
+        {
+            return Err(ValidationError::InvalidPassword);
+        }
+        // Display name is removed with `AttributeOp::Delete`, never set empty
+        if self
+            .display_name
+            .as_deref()
+            .is_some_and(|name| name.is_empty())
+        {
+            return Err(ValidationError::InvalidDisplayName);
+        }
+        if self.photo_url.as_deref().is_some_and(|url| !is_url(url)) {
+            return Err(ValidationError::InvalidPhotoUrl);
+        }
+        let phone_numbers = self.phone_number.iter().chain(
+            self.mfa
+                .iter()
+                .flat_map(|mfa| mfa.enrollments.iter().flatten())
+                .filter_map(|factor| factor.phone_info.as_ref()),
+        );
+        for phone_number in phone_numbers {
+            if !is_e164(phone_number) {
+                return Err(ValidationError::InvalidPhoneNumber);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub struct UserUpdateBuilder {
@@ -230,10 +568,10 @@ pub enum AttributeOp<T> {
 }
 
 impl UserUpdateBuilder {
-    pub fn new(uid: String) -> Self {
+    pub fn new(uid: Uid) -> Self {
         Self {
             update: UserUpdate {
-                uid,
+                uid: uid.into_inner(),
                 ..Default::default()
             },
         }
@@ -254,9 +592,11 @@ impl UserUpdateBuilder {
         self
     }
 
-    pub fn photo_url(mut self, value: AttributeOp<String>) -> Self {
+    pub fn photo_url(mut self, value: AttributeOp<PhotoUrl>) -> Self {
         match value {
-            AttributeOp::Change(new_photo_url) => self.update.photo_url = Some(new_photo_url),
+            AttributeOp::Change(new_photo_url) => {
+                self.update.photo_url = Some(new_photo_url.into())
+            }
             AttributeOp::Delete => self
                 .update
                 .delete_attribute
@@ -267,10 +607,10 @@ impl UserUpdateBuilder {
         self
     }
 
-    pub fn phone_number(mut self, value: AttributeOp<String>) -> Self {
+    pub fn phone_number(mut self, value: AttributeOp<PhoneNumber>) -> Self {
         match value {
             AttributeOp::Change(new_phone_number) => {
-                self.update.phone_number = Some(new_phone_number)
+                self.update.phone_number = Some(new_phone_number.into())
             }
             AttributeOp::Delete => self
                 .update
@@ -288,14 +628,14 @@ impl UserUpdateBuilder {
         self
     }
 
-    pub fn email(mut self, value: String) -> Self {
-        self.update.email = Some(value);
+    pub fn email(mut self, value: Email) -> Self {
+        self.update.email = Some(value.into());
 
         self
     }
 
-    pub fn password(mut self, value: String) -> Self {
-        self.update.password = Some(value);
+    pub fn password(mut self, value: Password) -> Self {
+        self.update.password = Some(value.into_inner());
 
         self
     }
@@ -312,6 +652,25 @@ impl UserUpdateBuilder {
         self
     }
 
+    /// Replace enrolled second factors, or remove all of them
+    pub fn multi_factor(mut self, value: AttributeOp<Vec<MultiFactorInfo>>) -> Self {
+        self.update.mfa = Some(match value {
+            AttributeOp::Change(enrollments) => MultiFactorEnrollments {
+                enrollments: Some(enrollments),
+            },
+            AttributeOp::Delete => MultiFactorEnrollments::default(),
+        });
+
+        self
+    }
+
+    /// Invalidate refresh tokens and sessions started before given time
+    pub fn valid_since(mut self, value: OffsetDateTime) -> Self {
+        self.update.valid_since = Some(value);
+
+        self
+    }
+
     pub fn build(self) -> UserUpdate {
         self.update
     }
@@ -332,10 +691,56 @@ struct UserIds {
     pub force: bool,
 }
 
+/// Failure of a single user in a batch operation
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchUserError {
+    /// Position of the user in the input of the whole operation
+    pub index: usize,
+    #[serde(rename = "localId")]
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    /// Client-side validation failure, errors reported by the server only carry a message
+    #[serde(skip)]
+    pub reason: Option<UserImportError>,
+}
+
+impl BatchUserError {
+    /// Error code from the message, e.g. `NOT_DISABLED`
+    pub fn error_code(&self) -> AuthErrorCode {
+        split_error_message(&self.message).0.into()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct DeleteUsersResponse {
+    #[serde(default)]
+    errors: Vec<BatchUserError>,
+}
+
+/// Outcome of deleting users in bulk
+#[derive(Debug, Clone, Default)]
+pub struct DeleteUsersResult {
+    pub success_count: usize,
+    pub failure_count: usize,
+    pub errors: Vec<BatchUserError>,
+}
+
 pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
     fn get_client(&self) -> &C;
     fn get_auth_uri_builder(&self) -> &ApiUriBuilder;
 
+    /// Signer used to mint custom tokens, custom tokens are not available without one
+    fn get_token_signer(&self) -> Option<&impl Signer> {
+        None::<&EmulatorSigner>
+    }
+
+    /// Tenant all calls are scoped to, if any
+    fn get_tenant_id(&self) -> Option<&str> {
+        None
+    }
+
     /// Creates a new user account with the specified properties.
     /// # Example
     /// ```rust
@@ -345,17 +750,31 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
     ///        "123ABC".into(),
     ///     )
     /// ).await.unwrap();
+    ///
+    /// let new_user = auth.create_user(
+    ///     NewUser::builder()
+    ///         .uid(Uid::new("user1")?)
+    ///         .email(Email::new("user1@example.com")?)
+    ///         .email_verified(true)
+    ///         .phone_number(PhoneNumber::new("+11234567890")?)
+    ///         .display_name("User One".into())
+    ///         .build()
+    /// ).await.unwrap();
     /// ```
     fn create_user(
         &self,
         user: NewUser,
     ) -> impl Future<Output = Result<User, Report<ApiClientError>>> + Send {
-        let client = self.get_client();
-        let uri = self
-            .get_auth_uri_builder()
-            .build(FirebaseAuthRestApi::CreateUser);
+        async move {
+            user.validate()
+                .map_err(|error| Report::new(ApiClientError::Validation(error)))?;
+            let client = self.get_client();
+            let uri = self
+                .get_auth_uri_builder()
+                .build(FirebaseAuthRestApi::CreateUser);
 
-        client.send_request_body(uri, Method::POST, user)
+            client.send_request_body(uri, Method::POST, user).await
+        }
     }
 
     /// Get first user that matches given identifier filter
@@ -412,6 +831,64 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
         }
     }
 
+    /// Get users for any number of identifiers, lookups are split into batches of
+    /// `MAX_GET_USERS_IDENTIFIERS` which run concurrently
+    /// # Example
+    /// ```rust
+    /// let result = auth.get_users_bulk(vec![
+    ///     UserIdentifier::Uid(Uid::new("A123456")?),
+    ///     UserIdentifier::Email(Email::new("me@example.com")?),
+    /// ]).await.unwrap();
+    ///
+    /// for identifier in result.not_found {
+    ///     println!("No user for {identifier:?}");
+    /// }
+    /// ```
+    fn get_users_bulk(
+        &self,
+        identifiers: Vec<UserIdentifier>,
+    ) -> impl Future<Output = Result<GetUsersResult, Report<ApiClientError>>> + Send {
+        async move {
+            let batches: Vec<Vec<UserIdentifier>> = identifiers
+                .chunks(MAX_GET_USERS_IDENTIFIERS)
+                .map(<[UserIdentifier]>::to_vec)
+                .collect();
+
+            let mut lookups = stream::iter(batches)
+                .map(|batch| async move {
+                    let ids = batch
+                        .iter()
+                        .cloned()
+                        .fold(UserIdentifiers::builder(), |ids, identifier| {
+                            ids.with_identifier(identifier)
+                        })
+                        .build();
+                    let users = self.get_users(ids).await?.unwrap_or_default();
+
+                    Ok::<_, Report<ApiClientError>>((batch, users))
+                })
+                .buffered(GET_USERS_BULK_CONCURRENCY);
+
+            let mut result = GetUsersResult::default();
+            let mut found_uids = HashSet::new();
+            while let Some((batch, users)) = lookups.try_next().await? {
+                result.not_found.extend(
+                    batch
+                        .into_iter()
+                        .filter(|identifier| !users.iter().any(|user| identifier.matches(user))),
+                );
+
+                for user in users {
+                    if found_uids.insert(user.uid.clone()) {
+                        result.found.push(user);
+                    }
+                }
+            }
+
+            Ok(result)
+        }
+    }
+
     /// Fetch all users in batches of `users_per_page`, to progress pass previous page into the method's `prev`.
     /// # Example
     /// ```rust
@@ -458,6 +935,52 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
         }
     }
 
+    /// Stream all users, pages of `page_size` users are fetched as the stream is consumed
+    /// # Example
+    /// ```rust
+    /// auth.users_stream(1000)
+    ///     .try_for_each_concurrent(10, |user| async move {
+    ///         println!("User: {user:?}");
+    ///         Ok(())
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    fn users_stream(
+        &self,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<User, Report<ApiClientError>>> + Send {
+        self.users_stream_with_prefetch(page_size, 0)
+    }
+
+    /// Stream all users like `users_stream`, fetching up to `prefetch_pages` pages ahead of the consumer
+    fn users_stream_with_prefetch(
+        &self,
+        page_size: usize,
+        prefetch_pages: usize,
+    ) -> impl Stream<Item = Result<User, Report<ApiClientError>>> + Send {
+        let pages = stream::try_unfold(
+            Some(None),
+            move |page_token: Option<Option<String>>| async move {
+                let Some(page_token) = page_token else {
+                    return Ok::<_, Report<ApiClientError>>(None);
+                };
+                let prev = page_token.map(|token| UserList {
+                    users: Vec::new(),
+                    next_page_token: Some(token),
+                });
+                let Some(page) = self.list_users(page_size, prev).await? else {
+                    return Ok(None);
+                };
+                let next_page_token = page.next_page_token.map(Some);
+
+                Ok(Some((page.users, next_page_token)))
+            },
+        );
+
+        Prefetch::new(pages, prefetch_pages)
+    }
+
     /// Delete user with given ID
     fn delete_user(
         &self,
@@ -477,33 +1000,57 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
         }
     }
 
-    /// Delete all users with given list of IDs
+    /// Delete all users with given list of IDs, sent in batches of `MAX_DELETE_USERS_BATCH`.
+    /// Unless `force` is set, only disabled users get deleted, failures are reported per user.
+    /// When a batch request fails, the error carries `DeleteUsersResult` of the preceding batches,
+    /// see `Report::downcast_ref`.
     fn delete_users(
         &self,
         uids: Vec<String>,
         force: bool,
-    ) -> impl Future<Output = Result<(), Report<ApiClientError>>> + Send {
+    ) -> impl Future<Output = Result<DeleteUsersResult, Report<ApiClientError>>> + Send {
         async move {
             let client = self.get_client();
             let uri_builder = self.get_auth_uri_builder();
+            let mut result = DeleteUsersResult::default();
+
+            for (batch_number, batch) in uids.chunks(MAX_DELETE_USERS_BATCH).enumerate() {
+                let response: DeleteUsersResponse = match client
+                    .send_request_body(
+                        uri_builder.build(FirebaseAuthRestApi::DeleteUsers),
+                        Method::POST,
+                        UserIds {
+                            uids: batch.to_vec(),
+                            force,
+                        },
+                    )
+                    .await
+                {
+                    Ok(response) => response,
+                    Err(report) => return Err(report.attach_opaque(result)),
+                };
+
+                result.failure_count += response.errors.len();
+                result.success_count += batch.len().saturating_sub(response.errors.len());
+                result
+                    .errors
+                    .extend(response.errors.into_iter().map(|mut error| {
+                        error.index += batch_number * MAX_DELETE_USERS_BATCH;
+                        error
+                    }));
+            }
 
-            client
-                .send_request_body_empty_response(
-                    uri_builder.build(FirebaseAuthRestApi::DeleteUsers),
-                    Method::POST,
-                    UserIds { uids, force },
-                )
-                .await
+            Ok(result)
         }
     }
 
     /// Update user with given changes
     /// # Example
     /// ```rust
-    /// let update = UserUpdate::builder("ID123".into())
+    /// let update = UserUpdate::builder(Uid::new("ID123")?)
     ///     .display_name(AttributeOp::Change("My new name".into()))
     ///     .phone_number(AttributeOp::Delete)
-    ///     .email("new@example.com".into())
+    ///     .email(Email::new("new@example.com")?)
     ///     .build();
     /// auth.update_user(update).await.unwrap();
     /// ```
@@ -512,6 +1059,9 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
         update: UserUpdate,
     ) -> impl Future<Output = Result<User, Report<ApiClientError>>> + Send {
         async move {
+            update
+                .validate()
+                .map_err(|error| Report::new(ApiClientError::Validation(error)))?;
             let client = self.get_client();
             let uri_builder = self.get_auth_uri_builder();
 
@@ -525,34 +1075,109 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
         }
     }
 
-    /// Create users in bulk
+    /// Revoke all refresh tokens of a user, sessions started before now will no longer be able to
+    /// obtain new ID tokens. Already issued ID tokens and session cookies stay valid until expiry,
+    /// unless verified with `TokenValidator::validate_and_check_revoked`.
+    fn revoke_refresh_tokens(
+        &self,
+        uid: String,
+    ) -> impl Future<Output = Result<(), Report<ApiClientError>>> + Send {
+        async move {
+            let uid =
+                Uid::new(uid).map_err(|error| Report::new(ApiClientError::Validation(error)))?;
+            let client = self.get_client();
+            let uri_builder = self.get_auth_uri_builder();
+
+            client
+                .send_request_body_empty_response(
+                    uri_builder.build(FirebaseAuthRestApi::UpdateUser),
+                    Method::POST,
+                    UserUpdate::builder(uid)
+                        .valid_since(OffsetDateTime::now_utc())
+                        .build(),
+                )
+                .await
+        }
+    }
+
+    /// Create users in bulk, records are sent in batches of `MAX_IMPORT_USERS_BATCH`.
+    /// Records failing client-side validation are not sent and reported as failures with a `reason`.
+    /// When a batch request fails, the error carries `UserImportResult` of the preceding batches
+    /// and of validation, see `Report::downcast_ref`.
     /// # Example
     /// ```rust
     /// let records = vec![
     ///     UserImportRecord::builder()
-    ///         .with_email("me@example.com".into(), true)
-    ///         .with_display_name("My Name".into())
+    ///         .with_uid(Uid::new("A123456")?)
+    ///         .with_email(Email::new("me@example.com")?, true)
+    ///         .with_password_hash("aGFzaA==".into(), Some("c2FsdA==".into()))
     ///         .build()
     /// ];
-    /// auth.import_users(records).await.unwrap();
+    /// let options = UserImportOptions::with_hash(PasswordHash::HmacSha256 {
+    ///     key: "a2V5".into(),
+    /// });
+    /// let result = auth.import_users(records, options).await.unwrap();
     /// ```
     fn import_users(
         &self,
         users: Vec<UserImportRecord>,
-    ) -> impl Future<Output = Result<(), Report<ApiClientError>>> + Send {
+        options: UserImportOptions,
+    ) -> impl Future<Output = Result<UserImportResult, Report<ApiClientError>>> + Send {
         async move {
             let client = self.get_client();
             let uri_builder = self.get_auth_uri_builder();
+            if let Some(hash) = &options.hash {
+                hash.validate()
+                    .change_context(ApiClientError::InvalidImportOptions)?;
+            }
 
-            client
-                .send_request_body_empty_response(
-                    uri_builder.build(FirebaseAuthRestApi::ImportUsers),
-                    Method::POST,
-                    UserImportRecords { users },
-                )
-                .await?;
+            let mut result = UserImportResult::default();
+            let mut valid_users = Vec::with_capacity(users.len());
+            for (index, record) in users.into_iter().enumerate() {
+                match record.validate(&options) {
+                    Ok(()) => valid_users.push((index, record)),
+                    Err(reason) => {
+                        result.failure_count += 1;
+                        result.errors.push(BatchUserError {
+                            index,
+                            uid: record.uid,
+                            message: reason.to_string(),
+                            reason: Some(reason),
+                        });
+                    }
+                }
+            }
 
-            Ok(())
+            for batch in valid_users.chunks(MAX_IMPORT_USERS_BATCH) {
+                let (indices, records): (Vec<usize>, Vec<UserImportRecord>) =
+                    batch.iter().cloned().unzip();
+                let response: UserImportResponse = match client
+                    .send_request_body(
+                        uri_builder.build(FirebaseAuthRestApi::ImportUsers),
+                        Method::POST,
+                        UserImportRecords::new(records, &options),
+                    )
+                    .await
+                {
+                    Ok(response) => response,
+                    Err(report) => {
+                        result.errors.sort_by_key(|error| error.index);
+                        return Err(report.attach_opaque(result));
+                    }
+                };
+
+                result.failure_count += response.errors.len();
+                result.success_count += batch.len().saturating_sub(response.errors.len());
+                result
+                    .errors
+                    .extend(response.errors.into_iter().map(|mut error| {
+                        error.index = indices.get(error.index).copied().unwrap_or(error.index);
+                        error
+                    }));
+            }
+            result.errors.sort_by_key(|error| error.index);
+
+            Ok(result)
         }
     }
 
@@ -613,6 +1238,35 @@ pub trait FirebaseAuthService<C: ApiHttpClient>: Send + Sync + 'static {
             Ok(session_cookie.session_cookie)
         }
     }
+
+    /// Create custom token for given user ID, that can be exchanged for an ID token by client SDKs.
+    /// Developer claims end up in the `claims` of the custom token and in the resulting ID token.
+    /// # Example
+    /// ```rust
+    /// let mut claims = Claims::default();
+    /// claims.get_mut().insert("premium".into(), Value::Bool(true));
+    ///
+    /// let token = auth.create_custom_token("ID123".into(), Some(claims)).await.unwrap();
+    /// ```
+    fn create_custom_token(
+        &self,
+        uid: String,
+        developer_claims: Option<Claims>,
+    ) -> impl Future<Output = Result<String, Report<CustomTokenError>>> + Send {
+        async move {
+            let signer = self
+                .get_token_signer()
+                .ok_or(Report::new(CustomTokenError::MissingSigner))?;
+
+            encode_custom_token(
+                signer,
+                &uid,
+                developer_claims.as_ref(),
+                self.get_tenant_id(),
+            )
+            .await
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -753,10 +1407,13 @@ where
     }
 }
 
-pub struct FirebaseAuth<ApiHttpClientT> {
+pub struct FirebaseAuth<ApiHttpClientT, SignerT = CredentialsSigner> {
     client: ApiHttpClientT,
     auth_uri_builder: ApiUriBuilder,
+    provider_config_uri_builder: ApiUriBuilder,
     emulator_auth_uri_builder: Option<ApiUriBuilder>,
+    signer: Option<SignerT>,
+    tenant_id: Option<String>,
 }
 
 impl<ApiHttpClientT> FirebaseAuth<ApiHttpClientT>
@@ -767,12 +1424,17 @@ where
     pub fn emulated(emulator_url: String, project_id: &str, client: ApiHttpClientT) -> Self {
         let fb_auth_root = emulator_url.clone()
             + &format!("/{FIREBASE_AUTH_REST_AUTHORITY}/v1/projects/{project_id}");
+        let fb_auth_v2_root = emulator_url.clone()
+            + &format!("/{FIREBASE_AUTH_REST_AUTHORITY}/v2/projects/{project_id}");
         let fb_emu_root = emulator_url + &format!("/emulator/v1/projects/{project_id}");
 
         Self {
             client,
             auth_uri_builder: ApiUriBuilder::new(fb_auth_root),
+            provider_config_uri_builder: ApiUriBuilder::new(fb_auth_v2_root),
             emulator_auth_uri_builder: Some(ApiUriBuilder::new(fb_emu_root)),
+            signer: Some(CredentialsSigner::Emulator(EmulatorSigner)),
+            tenant_id: None,
         }
     }
 
@@ -781,18 +1443,63 @@ where
         let fb_auth_root = "https://".to_string()
             + FIREBASE_AUTH_REST_AUTHORITY
             + &format!("/v1/projects/{project_id}");
+        let fb_auth_v2_root = "https://".to_string()
+            + FIREBASE_AUTH_REST_AUTHORITY
+            + &format!("/v2/projects/{project_id}");
 
         Self {
             client,
             auth_uri_builder: ApiUriBuilder::new(fb_auth_root),
+            provider_config_uri_builder: ApiUriBuilder::new(fb_auth_v2_root),
             emulator_auth_uri_builder: None,
+            signer: None,
+            tenant_id: None,
+        }
+    }
+}
+
+impl<ApiHttpClientT, SignerT> FirebaseAuth<ApiHttpClientT, SignerT>
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    /// Replace signer used for minting custom tokens
+    pub fn with_signer<NewSignerT: Signer>(
+        self,
+        signer: NewSignerT,
+    ) -> FirebaseAuth<ApiHttpClientT, NewSignerT> {
+        FirebaseAuth {
+            client: self.client,
+            auth_uri_builder: self.auth_uri_builder,
+            provider_config_uri_builder: self.provider_config_uri_builder,
+            emulator_auth_uri_builder: self.emulator_auth_uri_builder,
+            signer: Some(signer),
+            tenant_id: self.tenant_id,
+        }
+    }
+
+    /// Scope all calls to given Identity Platform tenant,
+    /// issued custom tokens are bound to the tenant too
+    pub fn for_tenant(self, tenant_id: &TenantId) -> Self {
+        let tenant_path = format!("/tenants/{tenant_id}");
+
+        Self {
+            client: self.client,
+            auth_uri_builder: self.auth_uri_builder.nested(&tenant_path),
+            provider_config_uri_builder: self.provider_config_uri_builder.nested(&tenant_path),
+            emulator_auth_uri_builder: self
+                .emulator_auth_uri_builder
+                .map(|builder| builder.nested(&tenant_path)),
+            signer: self.signer,
+            tenant_id: Some(tenant_id.to_string()),
         }
     }
 }
 
-impl<ApiHttpClientT> FirebaseAuthService<ApiHttpClientT> for FirebaseAuth<ApiHttpClientT>
+impl<ApiHttpClientT, SignerT> FirebaseAuthService<ApiHttpClientT>
+    for FirebaseAuth<ApiHttpClientT, SignerT>
 where
     ApiHttpClientT: ApiHttpClient + Send + Sync,
+    SignerT: Signer,
 {
     fn get_client(&self) -> &ApiHttpClientT {
         &self.client
@@ -801,11 +1508,36 @@ where
     fn get_auth_uri_builder(&self) -> &ApiUriBuilder {
         &self.auth_uri_builder
     }
+
+    fn get_token_signer(&self) -> Option<&impl Signer> {
+        self.signer.as_ref()
+    }
+
+    fn get_tenant_id(&self) -> Option<&str> {
+        self.tenant_id.as_deref()
+    }
+}
+
+impl<ApiHttpClientT, SignerT> ProviderConfigService<ApiHttpClientT>
+    for FirebaseAuth<ApiHttpClientT, SignerT>
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+    SignerT: Signer,
+{
+    fn get_provider_config_client(&self) -> &ApiHttpClientT {
+        &self.client
+    }
+
+    fn get_provider_config_uri_builder(&self) -> &ApiUriBuilder {
+        &self.provider_config_uri_builder
+    }
 }
 
-impl<ApiHttpClientT> FirebaseEmulatorAuthService<ApiHttpClientT> for FirebaseAuth<ApiHttpClientT>
+impl<ApiHttpClientT, SignerT> FirebaseEmulatorAuthService<ApiHttpClientT>
+    for FirebaseAuth<ApiHttpClientT, SignerT>
 where
     ApiHttpClientT: ApiHttpClient + Send + Sync,
+    SignerT: Signer,
 {
     fn get_emulator_client(&self) -> &ApiHttpClientT {
         &self.client
@@ -0,0 +1,601 @@
+//! OIDC and SAML identity provider configuration management
+
+#[cfg(test)]
+mod test;
+
+use crate::api_uri::{ApiUriBuilder, ProviderConfigRestApi};
+use crate::client::ApiHttpClient;
+use crate::client::error::ApiClientError;
+use crate::util::update_mask::{MaskedUpdate, non_empty_update_mask};
+use error_stack::Report;
+use http::Method;
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcResponseType {
+    /// Use implicit flow, receiving ID token directly
+    #[serde(default)]
+    pub id_token: bool,
+    /// Use authorization code flow, requires client secret
+    #[serde(default)]
+    pub code: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcProviderConfig {
+    /// Resource name, `projects/{project_id}/oauthIdpConfigs/{provider_id}`
+    pub name: String,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    pub client_id: String,
+    pub issuer: String,
+    pub client_secret: Option<String>,
+    pub response_type: Option<OidcResponseType>,
+}
+
+impl OidcProviderConfig {
+    /// Provider ID, always prefixed with `oidc.`
+    pub fn provider_id(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcProviderConfigList {
+    #[serde(default, rename = "oauthIdpConfigs")]
+    pub configs: Vec<OidcProviderConfig>,
+    pub next_page_token: Option<String>,
+}
+
+/// OIDC provider settings for creating a config or updating set fields of one
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcProviderConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_type: Option<OidcResponseType>,
+}
+
+impl OidcProviderConfigUpdate {
+    pub fn builder() -> OidcProviderConfigUpdateBuilder {
+        OidcProviderConfigUpdateBuilder::default()
+    }
+}
+
+impl MaskedUpdate for OidcProviderConfigUpdate {}
+
+#[derive(Default)]
+pub struct OidcProviderConfigUpdateBuilder {
+    update: OidcProviderConfigUpdate,
+}
+
+impl OidcProviderConfigUpdateBuilder {
+    pub fn display_name(mut self, value: String) -> Self {
+        self.update.display_name = Some(value);
+
+        self
+    }
+
+    pub fn enabled(mut self, value: bool) -> Self {
+        self.update.enabled = Some(value);
+
+        self
+    }
+
+    pub fn client_id(mut self, value: String) -> Self {
+        self.update.client_id = Some(value);
+
+        self
+    }
+
+    pub fn issuer(mut self, value: String) -> Self {
+        self.update.issuer = Some(value);
+
+        self
+    }
+
+    pub fn client_secret(mut self, value: String) -> Self {
+        self.update.client_secret = Some(value);
+
+        self
+    }
+
+    pub fn response_type(mut self, value: OidcResponseType) -> Self {
+        self.update.response_type = Some(value);
+
+        self
+    }
+
+    pub fn build(self) -> OidcProviderConfigUpdate {
+        self.update
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlIdpCertificate {
+    pub x509_certificate: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlIdpConfig {
+    pub idp_entity_id: String,
+    pub sso_url: String,
+    #[serde(default)]
+    pub idp_certificates: Vec<SamlIdpCertificate>,
+    #[serde(default)]
+    pub sign_request: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlSpConfig {
+    pub sp_entity_id: String,
+    pub callback_uri: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlProviderConfig {
+    /// Resource name, `projects/{project_id}/inboundSamlConfigs/{provider_id}`
+    pub name: String,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    pub idp_config: SamlIdpConfig,
+    pub sp_config: SamlSpConfig,
+}
+
+impl SamlProviderConfig {
+    /// Provider ID, always prefixed with `saml.`
+    pub fn provider_id(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlProviderConfigList {
+    #[serde(default, rename = "inboundSamlConfigs")]
+    pub configs: Vec<SamlProviderConfig>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlIdpConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idp_entity_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sso_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idp_certificates: Option<Vec<SamlIdpCertificate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign_request: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlSpConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sp_entity_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_uri: Option<String>,
+}
+
+/// SAML provider settings for creating a config or updating set fields of one
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlProviderConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idp_config: Option<SamlIdpConfigUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sp_config: Option<SamlSpConfigUpdate>,
+}
+
+impl SamlProviderConfigUpdate {
+    pub fn builder() -> SamlProviderConfigUpdateBuilder {
+        SamlProviderConfigUpdateBuilder::default()
+    }
+}
+
+impl MaskedUpdate for SamlProviderConfigUpdate {}
+
+#[derive(Default)]
+pub struct SamlProviderConfigUpdateBuilder {
+    update: SamlProviderConfigUpdate,
+}
+
+impl SamlProviderConfigUpdateBuilder {
+    fn idp_config(&mut self) -> &mut SamlIdpConfigUpdate {
+        self.update.idp_config.get_or_insert_default()
+    }
+
+    fn sp_config(&mut self) -> &mut SamlSpConfigUpdate {
+        self.update.sp_config.get_or_insert_default()
+    }
+
+    pub fn display_name(mut self, value: String) -> Self {
+        self.update.display_name = Some(value);
+
+        self
+    }
+
+    pub fn enabled(mut self, value: bool) -> Self {
+        self.update.enabled = Some(value);
+
+        self
+    }
+
+    /// Entity ID of the identity provider
+    pub fn idp_entity_id(mut self, value: String) -> Self {
+        self.idp_config().idp_entity_id = Some(value);
+
+        self
+    }
+
+    /// Single sign-on URL of the identity provider
+    pub fn sso_url(mut self, value: String) -> Self {
+        self.idp_config().sso_url = Some(value);
+
+        self
+    }
+
+    /// Replace PEM encoded certificates used to verify identity provider signatures
+    pub fn x509_certificates(mut self, value: Vec<String>) -> Self {
+        self.idp_config().idp_certificates = Some(
+            value
+                .into_iter()
+                .map(|x509_certificate| SamlIdpCertificate { x509_certificate })
+                .collect(),
+        );
+
+        self
+    }
+
+    /// Whether authentication requests are signed
+    pub fn sign_request(mut self, value: bool) -> Self {
+        self.idp_config().sign_request = Some(value);
+
+        self
+    }
+
+    /// Entity ID of the relying party, i.e. the service provider
+    pub fn rp_entity_id(mut self, value: String) -> Self {
+        self.sp_config().sp_entity_id = Some(value);
+
+        self
+    }
+
+    /// URL identity provider redirects to after authentication
+    pub fn callback_url(mut self, value: String) -> Self {
+        self.sp_config().callback_uri = Some(value);
+
+        self
+    }
+
+    pub fn build(self) -> SamlProviderConfigUpdate {
+        self.update
+    }
+}
+
+async fn create_config<C, T, R>(
+    client: &C,
+    uri: String,
+    id_param: &str,
+    provider_id: String,
+    config: T,
+) -> Result<R, Report<ApiClientError>>
+where
+    C: ApiHttpClient,
+    T: Serialize + Send,
+    R: DeserializeOwned + Send,
+{
+    client
+        .send_request_body(
+            uri + "?" + id_param + "=" + &urlencoding::encode(&provider_id),
+            Method::POST,
+            config,
+        )
+        .await
+}
+
+async fn update_config<C, T, R>(
+    client: &C,
+    uri: String,
+    update: T,
+) -> Result<R, Report<ApiClientError>>
+where
+    C: ApiHttpClient,
+    T: MaskedUpdate + Send,
+    R: DeserializeOwned + Send,
+{
+    let mask = non_empty_update_mask(&update)?;
+
+    client
+        .send_request_body(
+            uri + "?updateMask=" + &urlencoding::encode(&mask),
+            Method::PATCH,
+            update,
+        )
+        .await
+}
+
+async fn list_configs<C, R>(
+    client: &C,
+    uri: String,
+    configs_per_page: usize,
+    page_token: Option<String>,
+) -> Result<R, Report<ApiClientError>>
+where
+    C: ApiHttpClient,
+    R: DeserializeOwned + Send,
+{
+    let mut params = vec![("pageSize".to_string(), configs_per_page.to_string())];
+    if let Some(page_token) = page_token {
+        params.push(("pageToken".to_string(), page_token));
+    }
+
+    client
+        .send_request_with_params(uri, params.into_iter(), Method::GET)
+        .await
+}
+
+async fn delete_config<C: ApiHttpClient>(
+    client: &C,
+    uri: String,
+) -> Result<(), Report<ApiClientError>> {
+    let _: IgnoredAny = client.send_request(uri, Method::DELETE).await?;
+
+    Ok(())
+}
+
+/// Management of OIDC and SAML identity providers of a project or tenant
+pub trait ProviderConfigService<ApiHttpClientT>: Send + Sync + 'static
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    fn get_provider_config_client(&self) -> &ApiHttpClientT;
+    fn get_provider_config_uri_builder(&self) -> &ApiUriBuilder;
+
+    /// Register OIDC provider, `provider_id` must start with `oidc.`
+    /// # Example
+    /// ```rust
+    /// let config = auth.create_oidc_provider_config(
+    ///     "oidc.customer-a".into(),
+    ///     OidcProviderConfigUpdate::builder()
+    ///         .client_id("client-id".into())
+    ///         .issuer("https://idp.example.com".into())
+    ///         .enabled(true)
+    ///         .build()
+    /// ).await.unwrap();
+    /// ```
+    fn create_oidc_provider_config(
+        &self,
+        provider_id: String,
+        config: OidcProviderConfigUpdate,
+    ) -> impl Future<Output = Result<OidcProviderConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            create_config(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::OidcConfigs),
+                "oauthIdpConfigId",
+                provider_id,
+                config,
+            )
+            .await
+        }
+    }
+
+    fn get_oidc_provider_config(
+        &self,
+        provider_id: String,
+    ) -> impl Future<Output = Result<OidcProviderConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            self.get_provider_config_client()
+                .send_request(
+                    uri_builder.build(ProviderConfigRestApi::OidcConfigs) + "/" + &provider_id,
+                    Method::GET,
+                )
+                .await
+        }
+    }
+
+    /// Update fields of OIDC provider which are set in `update`, leaving others unchanged
+    fn update_oidc_provider_config(
+        &self,
+        provider_id: String,
+        update: OidcProviderConfigUpdate,
+    ) -> impl Future<Output = Result<OidcProviderConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            update_config(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::OidcConfigs) + "/" + &provider_id,
+                update,
+            )
+            .await
+        }
+    }
+
+    fn delete_oidc_provider_config(
+        &self,
+        provider_id: String,
+    ) -> impl Future<Output = Result<(), Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            delete_config(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::OidcConfigs) + "/" + &provider_id,
+            )
+            .await
+        }
+    }
+
+    /// List OIDC providers page by page, pass previous page to get the next one
+    fn list_oidc_provider_configs(
+        &self,
+        configs_per_page: usize,
+        prev: Option<OidcProviderConfigList>,
+    ) -> impl Future<Output = Result<Option<OidcProviderConfigList>, Report<ApiClientError>>> + Send
+    {
+        async move {
+            let page_token = match prev {
+                Some(OidcProviderConfigList {
+                    next_page_token: None,
+                    ..
+                }) => return Ok(None),
+                Some(prev) => prev.next_page_token,
+                None => None,
+            };
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            list_configs(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::OidcConfigs),
+                configs_per_page,
+                page_token,
+            )
+            .await
+            .map(Some)
+        }
+    }
+
+    /// Register SAML provider, `provider_id` must start with `saml.`
+    /// # Example
+    /// ```rust
+    /// let config = auth.create_saml_provider_config(
+    ///     "saml.customer-a".into(),
+    ///     SamlProviderConfigUpdate::builder()
+    ///         .idp_entity_id("https://idp.example.com".into())
+    ///         .sso_url("https://idp.example.com/sso".into())
+    ///         .x509_certificates(vec![certificate_pem])
+    ///         .rp_entity_id("my-project".into())
+    ///         .callback_url("https://my-project.firebaseapp.com/__/auth/handler".into())
+    ///         .enabled(true)
+    ///         .build()
+    /// ).await.unwrap();
+    /// ```
+    fn create_saml_provider_config(
+        &self,
+        provider_id: String,
+        config: SamlProviderConfigUpdate,
+    ) -> impl Future<Output = Result<SamlProviderConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            create_config(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::SamlConfigs),
+                "inboundSamlConfigId",
+                provider_id,
+                config,
+            )
+            .await
+        }
+    }
+
+    fn get_saml_provider_config(
+        &self,
+        provider_id: String,
+    ) -> impl Future<Output = Result<SamlProviderConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            self.get_provider_config_client()
+                .send_request(
+                    uri_builder.build(ProviderConfigRestApi::SamlConfigs) + "/" + &provider_id,
+                    Method::GET,
+                )
+                .await
+        }
+    }
+
+    /// Update fields of SAML provider which are set in `update`, leaving others unchanged
+    fn update_saml_provider_config(
+        &self,
+        provider_id: String,
+        update: SamlProviderConfigUpdate,
+    ) -> impl Future<Output = Result<SamlProviderConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            update_config(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::SamlConfigs) + "/" + &provider_id,
+                update,
+            )
+            .await
+        }
+    }
+
+    fn delete_saml_provider_config(
+        &self,
+        provider_id: String,
+    ) -> impl Future<Output = Result<(), Report<ApiClientError>>> + Send {
+        async move {
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            delete_config(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::SamlConfigs) + "/" + &provider_id,
+            )
+            .await
+        }
+    }
+
+    /// List SAML providers page by page, pass previous page to get the next one
+    fn list_saml_provider_configs(
+        &self,
+        configs_per_page: usize,
+        prev: Option<SamlProviderConfigList>,
+    ) -> impl Future<Output = Result<Option<SamlProviderConfigList>, Report<ApiClientError>>> + Send
+    {
+        async move {
+            let page_token = match prev {
+                Some(SamlProviderConfigList {
+                    next_page_token: None,
+                    ..
+                }) => return Ok(None),
+                Some(prev) => prev.next_page_token,
+                None => None,
+            };
+            let uri_builder = self.get_provider_config_uri_builder();
+
+            list_configs(
+                self.get_provider_config_client(),
+                uri_builder.build(ProviderConfigRestApi::SamlConfigs),
+                configs_per_page,
+                page_token,
+            )
+            .await
+            .map(Some)
+        }
+    }
+}
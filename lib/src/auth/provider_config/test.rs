@@ -0,0 +1,259 @@
+use super::{
+    OidcProviderConfig, OidcProviderConfigList, OidcProviderConfigUpdate, OidcResponseType,
+    ProviderConfigService, SamlProviderConfig, SamlProviderConfigList, SamlProviderConfigUpdate,
+};
+use crate::App;
+use crate::client::error::ApiClientError;
+use crate::util::test_server::TestServer;
+use crate::validation::TenantId;
+use serde_json::{Value, from_slice, json};
+
+const PROJECT_PATH: &str = "/identitytoolkit.googleapis.com/v2/projects/demo-firebase-project";
+
+fn oidc_config_json() -> Value {
+    json!({
+        "name": "projects/demo-firebase-project/oauthIdpConfigs/oidc.customer-a",
+        "displayName": "Customer A",
+        "enabled": true,
+        "clientId": "client-id",
+        "issuer": "https://idp.example.com",
+        "responseType": {"idToken": true}
+    })
+}
+
+fn saml_config_json() -> Value {
+    json!({
+        "name": "projects/demo-firebase-project/inboundSamlConfigs/saml.customer-b",
+        "enabled": true,
+        "idpConfig": {
+            "idpEntityId": "https://idp.example.com",
+            "ssoUrl": "https://idp.example.com/sso",
+            "idpCertificates": [{"x509Certificate": "-----BEGIN CERTIFICATE-----"}]
+        },
+        "spConfig": {
+            "spEntityId": "demo-firebase-project",
+            "callbackUri": "https://demo-firebase-project.firebaseapp.com/__/auth/handler"
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_create_oidc_provider_config() {
+    let (server, auth) =
+        TestServer::json_stub_client(oidc_config_json(), |url| App::emulated().auth(url)).await;
+
+    let config: OidcProviderConfig = auth
+        .create_oidc_provider_config(
+            "oidc.customer-a".into(),
+            OidcProviderConfigUpdate::builder()
+                .display_name("Customer A".into())
+                .client_id("client-id".into())
+                .issuer("https://idp.example.com".into())
+                .enabled(true)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(config.provider_id(), "oidc.customer-a");
+    assert!(config.enabled);
+    assert_eq!(
+        config.response_type,
+        Some(OidcResponseType {
+            id_token: true,
+            code: false
+        })
+    );
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(
+        request.path,
+        format!("{PROJECT_PATH}/oauthIdpConfigs?oauthIdpConfigId=oidc.customer-a")
+    );
+    assert_eq!(
+        from_slice::<Value>(&request.body).unwrap(),
+        json!({
+            "displayName": "Customer A",
+            "clientId": "client-id",
+            "issuer": "https://idp.example.com",
+            "enabled": true
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_update_oidc_provider_config() {
+    let (server, auth) =
+        TestServer::json_stub_client(oidc_config_json(), |url| App::emulated().auth(url)).await;
+
+    auth.update_oidc_provider_config(
+        "oidc.customer-a".into(),
+        OidcProviderConfigUpdate::builder()
+            .client_secret("secret".into())
+            .response_type(OidcResponseType {
+                id_token: false,
+                code: true,
+            })
+            .build(),
+    )
+    .await
+    .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "PATCH");
+    assert_eq!(
+        request.path,
+        format!(
+            "{PROJECT_PATH}/oauthIdpConfigs/oidc.customer-a?updateMask=clientSecret%2CresponseType.code%2CresponseType.idToken"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_tenant_oidc_provider_configs() {
+    let (server, auth) = TestServer::json_stub_client(
+        json!({
+            "oauthIdpConfigs": [oidc_config_json()],
+        }),
+        |url| App::emulated().auth(url),
+    )
+    .await;
+    let auth = auth.for_tenant(&TenantId::new("tenant-1").unwrap());
+
+    let page = auth
+        .list_oidc_provider_configs(10, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(page.configs.len(), 1);
+    assert!(page.next_page_token.is_none());
+
+    let last_page = OidcProviderConfigList {
+        configs: Vec::new(),
+        next_page_token: None,
+    };
+    assert!(
+        auth.list_oidc_provider_configs(10, Some(last_page))
+            .await
+            .unwrap()
+            .is_none()
+    );
+    auth.delete_oidc_provider_config("oidc.customer-a".into())
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[0].path,
+        format!("{PROJECT_PATH}/tenants/tenant-1/oauthIdpConfigs?pageSize=10")
+    );
+    assert_eq!(requests[1].method, "DELETE");
+    assert_eq!(
+        requests[1].path,
+        format!("{PROJECT_PATH}/tenants/tenant-1/oauthIdpConfigs/oidc.customer-a")
+    );
+}
+
+#[tokio::test]
+async fn test_create_saml_provider_config() {
+    let (server, auth) =
+        TestServer::json_stub_client(saml_config_json(), |url| App::emulated().auth(url)).await;
+
+    let config: SamlProviderConfig = auth
+        .create_saml_provider_config(
+            "saml.customer-b".into(),
+            SamlProviderConfigUpdate::builder()
+                .idp_entity_id("https://idp.example.com".into())
+                .sso_url("https://idp.example.com/sso".into())
+                .x509_certificates(vec!["-----BEGIN CERTIFICATE-----".into()])
+                .rp_entity_id("demo-firebase-project".into())
+                .callback_url(
+                    "https://demo-firebase-project.firebaseapp.com/__/auth/handler".into(),
+                )
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(config.provider_id(), "saml.customer-b");
+    assert_eq!(config.idp_config.idp_certificates.len(), 1);
+    assert!(!config.idp_config.sign_request);
+    assert_eq!(config.sp_config.sp_entity_id, "demo-firebase-project");
+
+    let request = &server.requests()[0];
+    assert_eq!(
+        request.path,
+        format!("{PROJECT_PATH}/inboundSamlConfigs?inboundSamlConfigId=saml.customer-b")
+    );
+    let body: Value = from_slice(&request.body).unwrap();
+    assert_eq!(
+        body["idpConfig"]["idpCertificates"][0]["x509Certificate"],
+        "-----BEGIN CERTIFICATE-----"
+    );
+    assert_eq!(body["spConfig"]["spEntityId"], "demo-firebase-project");
+}
+
+#[tokio::test]
+async fn test_update_and_list_saml_provider_configs() {
+    let (server, auth) = TestServer::json_stub_client(
+        json!({
+            "inboundSamlConfigs": [saml_config_json()],
+            "nextPageToken": "page-2",
+            "name": "projects/demo-firebase-project/inboundSamlConfigs/saml.customer-b",
+            "idpConfig": saml_config_json()["idpConfig"],
+            "spConfig": saml_config_json()["spConfig"],
+        }),
+        |url| App::emulated().auth(url),
+    )
+    .await;
+
+    auth.update_saml_provider_config(
+        "saml.customer-b".into(),
+        SamlProviderConfigUpdate::builder()
+            .enabled(false)
+            .sso_url("https://idp.example.com/sso2".into())
+            .build(),
+    )
+    .await
+    .unwrap();
+
+    let page: SamlProviderConfigList = auth
+        .list_saml_provider_configs(1, None)
+        .await
+        .unwrap()
+        .unwrap();
+    auth.list_saml_provider_configs(1, Some(page))
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].path,
+        format!(
+            "{PROJECT_PATH}/inboundSamlConfigs/saml.customer-b?updateMask=enabled%2CidpConfig.ssoUrl"
+        )
+    );
+    assert_eq!(
+        requests[2].path,
+        format!("{PROJECT_PATH}/inboundSamlConfigs?pageSize=1&pageToken=page-2")
+    );
+}
+
+#[tokio::test]
+async fn test_empty_provider_config_update_rejected() {
+    let (server, auth) =
+        TestServer::json_stub_client(oidc_config_json(), |url| App::emulated().auth(url)).await;
+
+    let err = auth
+        .update_oidc_provider_config(
+            "oidc.customer-a".into(),
+            OidcProviderConfigUpdate::default(),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err.current_context(), ApiClientError::EmptyUpdate));
+    assert!(server.requests().is_empty());
+}
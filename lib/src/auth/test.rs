@@ -1,12 +1,13 @@
-use super::import::{PasswordHash, UserImportRecord};
+use super::import::{PasswordHash, UserImportOptions, UserImportRecord};
 #[cfg(feature = "tokens")]
 // use super::token::jwt::JWToken;
 use super::{
     AttributeOp, Claims, FirebaseAuth, FirebaseAuthService, FirebaseEmulatorAuthService, NewUser,
-    OobCode, OobCodeAction, OobCodeActionType, UserIdentifiers, UserList, UserUpdate,
+    OobCode, OobCodeAction, OobCodeActionType, User, UserIdentifiers, UserList, UserUpdate,
 };
 use crate::App;
 use crate::client::ReqwestApiClient;
+use crate::validation::{Email, Password, PhoneNumber, PhotoUrl, TenantId, Uid};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serial_test::serial;
@@ -14,6 +15,7 @@ use std::collections::BTreeMap;
 
 #[cfg(feature = "tokens")]
 use time::Duration;
+use time::OffsetDateTime;
 use tokio;
 
 fn get_auth_service() -> FirebaseAuth<ReqwestApiClient> {
@@ -35,6 +37,13 @@ struct LoginResp {
     pub id_token: String,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomTokenLoginReq {
+    pub token: String,
+    pub return_secure_token: bool,
+}
+
 async fn _login(email: String, password: String) -> String {
     let client = reqwest::Client::builder().build().unwrap();
     let resp = client.post("http://emulator:9099/identitytoolkit.googleapis.com/v1/accounts:signInWithPassword?key=123")
@@ -197,20 +206,20 @@ async fn test_get_user() {
     .unwrap();
 
     let ids = UserIdentifiers::builder()
-        .with_email("test2@example.com".into())
+        .with_email(Email::new("test2@example.com").unwrap())
         .build();
 
     let user = auth.get_user(ids.clone()).await.unwrap();
     assert!(user.is_none(), "Should not match any user");
 
     let ids = UserIdentifiers::builder()
-        .with_email("test@example.com".into())
+        .with_email(Email::new("test@example.com").unwrap())
         .build();
     let user = auth.get_user(ids.clone()).await.unwrap().unwrap();
     assert_eq!(user.email.as_ref().unwrap(), "test@example.com");
 
     let ids = UserIdentifiers::builder()
-        .with_uid(user.uid.clone())
+        .with_uid(Uid::new(user.uid.clone()).unwrap())
         .build();
     let user = auth.get_user(ids.clone()).await.unwrap().unwrap();
     assert_eq!(user.email.as_ref().unwrap(), "test@example.com");
@@ -233,7 +242,9 @@ async fn test_delete_user() {
 
     auth.delete_user(user.uid.clone()).await.unwrap();
 
-    let ids = UserIdentifiers::builder().with_uid(user.uid).build();
+    let ids = UserIdentifiers::builder()
+        .with_uid(Uid::new(user.uid).unwrap())
+        .build();
 
     let user = auth.get_user(ids).await.unwrap();
     assert!(user.is_none(), "User did not get deleted");
@@ -259,13 +270,15 @@ async fn test_delete_users() {
         .await
         .unwrap();
 
-    auth.delete_users(vec![user.uid.clone(), user2.uid.clone()], true)
+    let result = auth
+        .delete_users(vec![user.uid.clone(), user2.uid.clone()], true)
         .await
         .unwrap();
+    assert_eq!(result.success_count, 2);
 
     let ids = UserIdentifiers::builder()
-        .with_uid(user.uid)
-        .with_uid(user2.uid)
+        .with_uid(Uid::new(user.uid).unwrap())
+        .with_uid(Uid::new(user2.uid).unwrap())
         .build();
 
     let users = auth.get_users(ids).await.unwrap();
@@ -290,20 +303,26 @@ async fn test_update_user() {
         .get_mut()
         .insert("hello".into(), Value::String("world".into()));
 
-    let update = UserUpdate::builder(user.uid.clone())
+    let update = UserUpdate::builder(Uid::new(user.uid.clone()).unwrap())
         .display_name(AttributeOp::Change("A test user".into()))
-        .photo_url(AttributeOp::Change("http://localhost/me.jpg".into()))
-        .phone_number(AttributeOp::Change("+1234567".into()))
+        .photo_url(AttributeOp::Change(
+            PhotoUrl::new("http://localhost/me.jpg").unwrap(),
+        ))
+        .phone_number(AttributeOp::Change(PhoneNumber::new("+1234567").unwrap()))
         .custom_claims(claims.clone())
-        .email("new@example.com".into())
-        .password("ABC123".into())
+        .email(Email::new("new@example.com").unwrap())
+        .password(Password::new("ABC123").unwrap())
         .email_verified(false)
         .disabled(true)
         .build();
 
     auth.update_user(update).await.unwrap();
     let user = auth
-        .get_user(UserIdentifiers::builder().with_uid(user.uid).build())
+        .get_user(
+            UserIdentifiers::builder()
+                .with_uid(Uid::new(user.uid).unwrap())
+                .build(),
+        )
         .await
         .unwrap()
         .unwrap();
@@ -323,7 +342,7 @@ async fn test_update_user() {
         &expected_password_hash
     );
 
-    let update = UserUpdate::builder(user.uid.clone())
+    let update = UserUpdate::builder(Uid::new(user.uid.clone()).unwrap())
         .display_name(AttributeOp::Delete)
         .photo_url(AttributeOp::Delete)
         .phone_number(AttributeOp::Delete)
@@ -331,7 +350,11 @@ async fn test_update_user() {
 
     auth.update_user(update).await.unwrap();
     let user = auth
-        .get_user(UserIdentifiers::builder().with_uid(user.uid).build())
+        .get_user(
+            UserIdentifiers::builder()
+                .with_uid(Uid::new(user.uid).unwrap())
+                .build(),
+        )
         .await
         .unwrap()
         .unwrap();
@@ -343,64 +366,60 @@ async fn test_update_user() {
     auth.clear_all_users().await.unwrap();
 }
 
+#[tokio::test]
+#[serial]
+async fn test_revoke_refresh_tokens() {
+    let auth = get_auth_service();
+
+    let user = auth
+        .create_user(NewUser::email_and_password(
+            "test@example.com".into(),
+            "123ABC".into(),
+        ))
+        .await
+        .unwrap();
+
+    let before_revoke = OffsetDateTime::now_utc().unix_timestamp();
+    auth.revoke_refresh_tokens(user.uid.clone()).await.unwrap();
+
+    let user = auth
+        .get_user(
+            UserIdentifiers::builder()
+                .with_uid(Uid::new(user.uid).unwrap())
+                .build(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let valid_since = user.metadata.valid_since.unwrap();
+
+    assert!(valid_since.unix_timestamp() >= before_revoke);
+
+    auth.clear_all_users().await.unwrap();
+}
+
 #[tokio::test]
 #[serial]
 async fn test_import_users() {
-    let passwords = vec![
-        PasswordHash::HmacSha512 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            key: "321".into(),
-        },
-        PasswordHash::HmacSha256 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            key: "321".into(),
-        },
-        PasswordHash::HmacSha1 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            key: "321".into(),
-        },
-        PasswordHash::HmacMd5 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            key: "321".into(),
-        },
-        PasswordHash::Sha256 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            rounds: 1,
-        },
-        PasswordHash::Sha512 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            rounds: 1,
-        },
-        PasswordHash::Ppkdf2Sha1 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            rounds: 1,
-        },
-        PasswordHash::Ppkdf2Sha256 {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
-            rounds: 1,
-        },
+    let hashes = vec![
+        PasswordHash::HmacSha512 { key: "MzIx".into() },
+        PasswordHash::HmacSha256 { key: "MzIx".into() },
+        PasswordHash::HmacSha1 { key: "MzIx".into() },
+        PasswordHash::HmacMd5 { key: "MzIx".into() },
+        PasswordHash::Sha256 { rounds: 1 },
+        PasswordHash::Sha512 { rounds: 1 },
+        PasswordHash::PbkdfSha1 { rounds: 1 },
+        PasswordHash::Pbkdf2Sha256 { rounds: 1 },
         PasswordHash::Scrypt {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
             rounds: 1,
-            key: "321".into(),
+            key: "MzIx".into(),
             memory_cost: 1,
-            salt_separator: Some("_".into()),
+            salt_separator: Some("Xw==".into()),
         },
         PasswordHash::StandardScrypt {
-            hash: "ABC".into(),
-            salt: Some("123".into()),
             block_size: 8,
             parallelization: 2,
-            memory_cost: 1,
+            memory_cost: 1024,
             dk_len: 12,
         },
     ];
@@ -410,29 +429,35 @@ async fn test_import_users() {
         .get_mut()
         .insert("foo".into(), Value::String("bar".into()));
 
-    let mut records: Vec<UserImportRecord> = Vec::with_capacity(passwords.len());
-    for (i, password) in passwords.iter().enumerate() {
+    let auth = get_auth_service();
+
+    // Each batch carries a single hash configuration
+    for (i, hash) in hashes.iter().enumerate() {
         let record = UserImportRecord::builder()
-            .with_uid(i.to_string())
-            .with_email(format!("{i}@example.com"), true)
+            .with_uid(Uid::new(i.to_string()).unwrap())
+            .with_email(Email::new(format!("{i}@example.com")).unwrap(), true)
             .with_display_name(format!("User {i}"))
-            .with_photo_url("http://localhost/me.jpg".into())
-            .with_phone_number("+123".into())
+            .with_photo_url(PhotoUrl::new("http://localhost/me.jpg").unwrap())
+            .with_phone_number(PhoneNumber::new(format!("+1555000{i:04}")).unwrap())
             .with_custom_claims(claims.clone())
             .with_being_disabled()
-            .with_password(password.clone())
+            .with_password_hash("QUJD".into(), Some("MTIz".into()))
             .build();
 
-        records.push(record);
+        let result = auth
+            .import_users(vec![record], UserImportOptions::with_hash(hash.clone()))
+            .await
+            .unwrap();
+        assert_eq!(result.failure_count, 0);
     }
 
-    let auth = get_auth_service();
-
-    auth.import_users(records).await.unwrap();
-
-    for (i, _) in passwords.iter().enumerate() {
+    for (i, _) in hashes.iter().enumerate() {
         let user = auth
-            .get_user(UserIdentifiers::builder().with_uid(i.to_string()).build())
+            .get_user(
+                UserIdentifiers::builder()
+                    .with_uid(Uid::new(i.to_string()).unwrap())
+                    .build(),
+            )
             .await
             .unwrap()
             .unwrap();
@@ -441,11 +466,11 @@ async fn test_import_users() {
         assert_eq!(user.email.unwrap(), format!("{i}@example.com"));
         assert_eq!(user.display_name.unwrap(), format!("User {i}"));
         assert_eq!(user.photo_url.unwrap(), "http://localhost/me.jpg");
-        assert_eq!(user.phone_number.unwrap(), "+123");
+        assert_eq!(user.phone_number.unwrap(), format!("+1555000{i:04}"));
         assert_eq!(&user.custom_claims.unwrap(), &claims);
         assert!(user.disabled.unwrap());
-        assert_eq!(user.password_hash.unwrap(), "ABC");
-        assert_eq!(user.salt.unwrap(), "123");
+        assert_eq!(user.password_hash.unwrap(), "QUJD");
+        assert_eq!(user.salt.unwrap(), "MTIz");
     }
 
     auth.clear_all_users().await.unwrap();
@@ -528,7 +553,7 @@ async fn test_generate_email_action_link() {
 #[tokio::test]
 #[serial]
 async fn test_create_session_cookie() {
-    use crate::jwt::{EmulatorValidator, TokenValidator};
+    use crate::jwt::TokenValidator;
     let auth = get_auth_service();
 
     auth.create_user(NewUser::email_and_password(
@@ -544,9 +569,557 @@ async fn test_create_session_cookie() {
         .await
         .unwrap();
 
-    let claims = EmulatorValidator.validate(&cookie).await.unwrap();
-    let email = claims.get("email").unwrap().as_str().unwrap();
-    assert_eq!(email, "test@example.com");
+    let decoded = App::emulated()
+        .cookie_token_verifier()
+        .validate(&cookie)
+        .await
+        .unwrap();
+    assert_eq!(decoded.email.unwrap(), "test@example.com");
 
     auth.clear_all_users().await.unwrap();
 }
+
+#[tokio::test]
+#[serial]
+async fn test_create_custom_token() {
+    let auth = get_auth_service();
+
+    let mut claims = Claims::default();
+    claims.get_mut().insert("premium".into(), Value::Bool(true));
+
+    let token = auth
+        .create_custom_token("custom-uid".into(), Some(claims))
+        .await
+        .unwrap();
+
+    let client = reqwest::Client::builder().build().unwrap();
+    let resp = client.post("http://emulator:9099/identitytoolkit.googleapis.com/v1/accounts:signInWithCustomToken?key=123")
+        .json(&CustomTokenLoginReq {
+            token,
+            return_secure_token: true,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "Custom token was rejected");
+
+    let user = auth
+        .get_user(
+            UserIdentifiers::builder()
+                .with_uid(Uid::new("custom-uid").unwrap())
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(
+        user.is_some(),
+        "Signing in with custom token did not create user"
+    );
+
+    auth.clear_all_users().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tenant_scoped_requests() {
+    use crate::util::test_server::{StubResponse, TestServer};
+
+    let server = TestServer::start(|_| StubResponse::json("{}".into())).await;
+    let auth = App::emulated().tenant_auth(server.url.clone(), &TenantId::new("tenant-1").unwrap());
+
+    let ids = UserIdentifiers::builder()
+        .with_uid(Uid::new("user1").unwrap())
+        .build();
+    assert!(auth.get_user(ids).await.unwrap().is_none());
+    auth.clear_all_users().await.unwrap();
+
+    let requests = server.requests();
+    assert!(
+        requests[0]
+            .path
+            .ends_with("/v1/projects/demo-firebase-project/tenants/tenant-1/accounts:lookup"),
+        "{}",
+        requests[0].path
+    );
+    assert!(
+        requests[1]
+            .path
+            .ends_with("/emulator/v1/projects/demo-firebase-project/tenants/tenant-1/accounts")
+    );
+
+    let token = auth
+        .create_custom_token("user1".into(), None)
+        .await
+        .unwrap();
+    assert_eq!(token.split('.').count(), 3);
+}
+
+#[tokio::test]
+async fn test_multi_factor_enrollments() {
+    use super::MultiFactorInfo;
+    use crate::util::test_server::{StubResponse, TestServer};
+    use serde_json::{from_slice, json};
+
+    let server = TestServer::start(|request| {
+        if request.path.ends_with("accounts:update") {
+            return StubResponse::json(json!({"localId": "user1"}).to_string());
+        }
+        StubResponse::json(
+            json!({"users": [{
+                "localId": "user1",
+                "mfaInfo": [
+                    {
+                        "mfaEnrollmentId": "enrollment-1",
+                        "displayName": "Work phone",
+                        "phoneInfo": "+11234567890",
+                        "enrolledAt": "2024-05-01T10:00:00.123Z"
+                    },
+                    {"mfaEnrollmentId": "enrollment-2", "totpInfo": {}}
+                ]
+            }]})
+            .to_string(),
+        )
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let ids = UserIdentifiers::builder()
+        .with_uid(Uid::new("user1").unwrap())
+        .build();
+    let user = auth.get_user(ids).await.unwrap().unwrap();
+    let mfa_info = user.mfa_info.unwrap();
+    assert_eq!(mfa_info[0].phone_info.as_deref(), Some("+11234567890"));
+    assert_eq!(
+        mfa_info[0].enrolled_at.unwrap().unix_timestamp(),
+        1714557600
+    );
+    assert!(mfa_info[1].totp_info.is_some());
+    assert!(mfa_info[1].phone_info.is_none());
+
+    let update = UserUpdate::builder(Uid::new("user1").unwrap())
+        .multi_factor(AttributeOp::Change(vec![MultiFactorInfo::phone(
+            PhoneNumber::new("+11234567890").unwrap(),
+            Some("Work phone".into()),
+        )]))
+        .build();
+    auth.update_user(update).await.unwrap();
+    let clear = UserUpdate::builder(Uid::new("user1").unwrap())
+        .multi_factor(AttributeOp::Delete)
+        .build();
+    auth.update_user(clear).await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+        from_slice::<Value>(&requests[1].body).unwrap()["mfa"],
+        json!({"enrollments": [{"displayName": "Work phone", "phoneInfo": "+11234567890"}]})
+    );
+    assert_eq!(
+        from_slice::<Value>(&requests[2].body).unwrap()["mfa"],
+        json!({})
+    );
+
+    let record = UserImportRecord::builder()
+        .with_uid(Uid::new("user2").unwrap())
+        .with_multi_factor(mfa_info[..1].to_vec())
+        .build();
+    assert_eq!(
+        serde_json::to_value(record).unwrap()["mfaInfo"][0]["enrolledAt"],
+        "2024-05-01T10:00:00.123Z"
+    );
+}
+
+#[test]
+fn test_user_record_fields() {
+    use super::User;
+    use serde_json::json;
+
+    let user: User = serde_json::from_value(json!({
+        "localId": "user1",
+        "tenantId": "tenant-1",
+        "email": "new@example.com",
+        "initialEmail": "old@example.com",
+        "customAuth": true,
+        "createdAt": "1714557600000",
+        "lastLoginAt": "1714557601000",
+        "lastRefreshAt": "2024-05-01T10:00:02.500Z",
+        "passwordUpdatedAt": 1714557603000u64,
+        "validSince": "1714557604",
+        "providerUserInfo": [{
+            "providerId": "google.com",
+            "rawId": "123",
+            "displayName": "User One",
+            "photoUrl": "https://example.com/photo.png",
+            "email": "new@example.com"
+        }]
+    }))
+    .unwrap();
+
+    assert_eq!(user.tenant_id.as_deref(), Some("tenant-1"));
+    assert_eq!(user.initial_email.as_deref(), Some("old@example.com"));
+    assert_eq!(user.custom_auth, Some(true));
+
+    let metadata = user.metadata;
+    let ts = |dt: Option<OffsetDateTime>| dt.unwrap().unix_timestamp();
+    assert_eq!(ts(metadata.created_at), 1714557600);
+    assert_eq!(ts(metadata.last_login_at), 1714557601);
+    assert_eq!(ts(metadata.last_refresh_at), 1714557602);
+    assert_eq!(ts(metadata.password_updated_at), 1714557603);
+    assert_eq!(ts(metadata.valid_since), 1714557604);
+
+    let provider = &user.provider_user_info.unwrap()[0];
+    assert_eq!(provider.display_name.as_deref(), Some("User One"));
+    assert_eq!(
+        provider.photo_url.as_deref(),
+        Some("https://example.com/photo.png")
+    );
+}
+
+#[tokio::test]
+async fn test_users_stream() {
+    use crate::util::test_server::{StubResponse, TestServer};
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    let server = TestServer::start(|request| {
+        let page = if request.path.contains("nextPageToken=page-3") {
+            json!({"users": [{"localId": "user5"}]})
+        } else if request.path.contains("nextPageToken=page-2") {
+            json!({"users": [{"localId": "user3"}, {"localId": "user4"}], "nextPageToken": "page-3"})
+        } else {
+            json!({"users": [{"localId": "user1"}, {"localId": "user2"}], "nextPageToken": "page-2"})
+        };
+        StubResponse::json(page.to_string())
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let uids: Vec<String> = auth
+        .users_stream(2)
+        .map_ok(|user| user.uid)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(uids, vec!["user1", "user2", "user3", "user4", "user5"]);
+    assert_eq!(server.requests().len(), 3);
+
+    let prefetched: Vec<User> = auth
+        .users_stream_with_prefetch(2, 2)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(prefetched.len(), 5);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 6);
+    assert!(requests[3].path.ends_with("accounts:batchGet?maxResults=2"));
+    assert!(
+        requests[5]
+            .path
+            .ends_with("accounts:batchGet?maxResults=2&nextPageToken=page-3")
+    );
+}
+
+#[tokio::test]
+async fn test_get_users_bulk() {
+    use super::{MAX_GET_USERS_IDENTIFIERS, UserIdentifier};
+    use crate::util::test_server::{StubResponse, TestServer};
+    use serde_json::{from_slice, json};
+
+    // Every even uid exists, the provider identifier resolves to user0
+    let server = TestServer::start(|request| {
+        let ids: Value = from_slice(&request.body).unwrap();
+        let mut users: Vec<Value> = ids["localId"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|uid| {
+                let uid = uid.as_str().unwrap();
+                let n: usize = uid.trim_start_matches("user").parse().unwrap();
+                n.is_multiple_of(2).then(|| json!({"localId": uid}))
+            })
+            .collect();
+        if ids.get("federatedUserId").is_some() {
+            users.push(json!({
+                "localId": "user0",
+                "providerUserInfo": [{"providerId": "google.com", "rawId": "g-0"}]
+            }));
+        }
+        StubResponse::json(json!({ "users": users }).to_string())
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let mut identifiers: Vec<UserIdentifier> = (0..250)
+        .map(|n| UserIdentifier::Uid(Uid::new(format!("user{n}")).unwrap()))
+        .collect();
+    identifiers.push(UserIdentifier::Provider {
+        provider_id: "google.com".into(),
+        raw_id: "g-0".into(),
+    });
+    identifiers.push(UserIdentifier::Email(
+        Email::new("missing@example.com").unwrap(),
+    ));
+
+    let result = auth.get_users_bulk(identifiers).await.unwrap();
+
+    assert_eq!(result.found.len(), 125);
+    assert_eq!(result.not_found.len(), 126);
+    assert!(result.not_found.contains(&UserIdentifier::Email(
+        Email::new("missing@example.com").unwrap()
+    )));
+    assert!(
+        !result
+            .not_found
+            .contains(&UserIdentifier::Uid(Uid::new("user0").unwrap()))
+    );
+    assert!(
+        result
+            .not_found
+            .contains(&UserIdentifier::Uid(Uid::new("user249").unwrap()))
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    for request in &requests {
+        let ids: Value = from_slice(&request.body).unwrap();
+        let count: usize = ["localId", "email", "federatedUserId"]
+            .iter()
+            .filter_map(|field| ids[field].as_array().map(Vec::len))
+            .sum();
+        assert!(count <= MAX_GET_USERS_IDENTIFIERS);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_results_and_chunking() {
+    use super::MAX_DELETE_USERS_BATCH;
+    use super::import::MAX_IMPORT_USERS_BATCH;
+    use crate::client::error::AuthErrorCode;
+    use crate::util::test_server::{StubResponse, TestServer};
+    use serde_json::{from_slice, json};
+
+    let server = TestServer::start(|request| {
+        let body: Value = from_slice(&request.body).unwrap();
+        let response = if request.path.ends_with("accounts:batchDelete") {
+            let first = &body["localIds"][0];
+            if first == "user1000" {
+                json!({"errors": [{"index": 3, "localId": "user1003", "message": "NOT_DISABLED"}]})
+            } else {
+                json!({})
+            }
+        } else if body["users"].as_array().unwrap().len() == 1 {
+            json!({"error": [{"index": 0, "message": "Invalid email"}]})
+        } else {
+            json!({})
+        };
+        StubResponse::json(response.to_string())
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let uids: Vec<String> = (0..1500).map(|n| format!("user{n}")).collect();
+    let deleted = auth.delete_users(uids, false).await.unwrap();
+    assert_eq!(deleted.success_count, 1499);
+    assert_eq!(deleted.failure_count, 1);
+    assert_eq!(deleted.errors[0].index, 1003);
+    assert_eq!(deleted.errors[0].uid.as_deref(), Some("user1003"));
+    assert_eq!(deleted.errors[0].message, "NOT_DISABLED");
+    assert_eq!(deleted.errors[0].error_code(), AuthErrorCode::NotDisabled);
+
+    let records: Vec<UserImportRecord> = (0..=MAX_IMPORT_USERS_BATCH)
+        .map(|n| {
+            UserImportRecord::builder()
+                .with_uid(Uid::new(n.to_string()).unwrap())
+                .build()
+        })
+        .collect();
+    let imported = auth
+        .import_users(records, UserImportOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(imported.success_count, MAX_IMPORT_USERS_BATCH);
+    assert_eq!(imported.failure_count, 1);
+    assert_eq!(imported.errors[0].index, MAX_IMPORT_USERS_BATCH);
+    assert_eq!(imported.errors[0].message, "Invalid email");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    let first_delete: Value = from_slice(&requests[0].body).unwrap();
+    assert_eq!(
+        first_delete["localIds"].as_array().unwrap().len(),
+        MAX_DELETE_USERS_BATCH
+    );
+}
+
+#[tokio::test]
+async fn test_batch_failure_keeps_partial_results() {
+    use super::import::{MAX_IMPORT_USERS_BATCH, UserImportResult};
+    use super::{DeleteUsersResult, MAX_DELETE_USERS_BATCH};
+    use crate::util::test_server::{StubResponse, TestServer};
+    use serde_json::from_slice;
+
+    let server = TestServer::start(|request| {
+        let body: Value = from_slice(&request.body).unwrap();
+        let second_batch =
+            body["localIds"][0] == "user1000" || body["users"][0]["localId"] == "1000";
+        if second_batch {
+            StubResponse {
+                status: 503,
+                headers: vec![("content-type".into(), "application/json".into())],
+                body: r#"{"error": {"code": 503, "message": "UNAVAILABLE", "errors": []}}"#.into(),
+            }
+        } else {
+            StubResponse::json("{}".into())
+        }
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let uids: Vec<String> = (0..1500).map(|n| format!("user{n}")).collect();
+    let err = auth.delete_users(uids, true).await.unwrap_err();
+    let deleted = err.downcast_ref::<DeleteUsersResult>().unwrap();
+    assert_eq!(deleted.success_count, MAX_DELETE_USERS_BATCH);
+    assert_eq!(deleted.failure_count, 0);
+
+    let records: Vec<UserImportRecord> = (0..=MAX_IMPORT_USERS_BATCH)
+        .map(|n| {
+            UserImportRecord::builder()
+                .with_uid(Uid::new(n.to_string()).unwrap())
+                .build()
+        })
+        .collect();
+    let err = auth
+        .import_users(records, UserImportOptions::default())
+        .await
+        .unwrap_err();
+    let imported = err.downcast_ref::<UserImportResult>().unwrap();
+    assert_eq!(imported.success_count, MAX_IMPORT_USERS_BATCH);
+    assert_eq!(imported.failure_count, 0);
+}
+
+#[tokio::test]
+async fn test_create_user_with_attributes() {
+    use super::MultiFactorInfo;
+    use crate::client::error::ApiClientError;
+    use crate::util::test_server::{StubResponse, TestServer};
+    use crate::validation::ValidationError;
+    use serde_json::{from_slice, json};
+
+    let server = TestServer::start(|_| {
+        StubResponse::json(json!({"localId": "user1", "email": "user1@example.com"}).to_string())
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let user = NewUser::builder()
+        .uid(Uid::new("user1").unwrap())
+        .email(Email::new("user1@example.com").unwrap())
+        .email_verified(true)
+        .password(Password::new("123456").unwrap())
+        .display_name("User One".into())
+        .photo_url(PhotoUrl::new("https://example.com/photo.png").unwrap())
+        .phone_number(PhoneNumber::new("+11234567890").unwrap())
+        .disabled(true)
+        .multi_factor(vec![MultiFactorInfo::phone(
+            PhoneNumber::new("+11234567890").unwrap(),
+            None,
+        )])
+        .build();
+    auth.create_user(user).await.unwrap();
+
+    let body: Value = from_slice(&server.requests()[0].body).unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "localId": "user1",
+            "email": "user1@example.com",
+            "emailVerified": true,
+            "password": "123456",
+            "displayName": "User One",
+            "photoUrl": "https://example.com/photo.png",
+            "phoneNumber": "+11234567890",
+            "disabled": true,
+            "mfaInfo": [{"phoneInfo": "+11234567890"}]
+        })
+    );
+
+    let invalid = [
+        (
+            NewUser::email_and_password("user1".into(), "123456".into()),
+            ValidationError::InvalidEmail,
+        ),
+        (
+            NewUser::email_and_password("user1@example.com".into(), "12345".into()),
+            ValidationError::InvalidPassword,
+        ),
+        (
+            NewUser {
+                phone_number: Some("11234567890".into()),
+                ..Default::default()
+            },
+            ValidationError::InvalidPhoneNumber,
+        ),
+    ];
+    for (user, error) in invalid {
+        assert_eq!(user.validate(), Err(error));
+        let report = auth.create_user(user).await.unwrap_err();
+        assert!(matches!(
+            report.current_context(),
+            ApiClientError::Validation(reported) if *reported == error
+        ));
+    }
+
+    let mut update = UserUpdate::builder(Uid::new("user1").unwrap()).build();
+    update.photo_url = Some("/photo.png".into());
+    assert_eq!(update.validate(), Err(ValidationError::InvalidPhotoUrl));
+    let report = auth.update_user(update).await.unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        ApiClientError::Validation(ValidationError::InvalidPhotoUrl)
+    ));
+
+    let update = UserUpdate::builder(Uid::new("user1").unwrap())
+        .display_name(AttributeOp::Change(String::new()))
+        .build();
+    assert_eq!(update.validate(), Err(ValidationError::InvalidDisplayName));
+
+    let mut update = UserUpdate::builder(Uid::new("user1").unwrap()).build();
+    update.uid = String::new();
+    assert_eq!(update.validate(), Err(ValidationError::InvalidUid));
+    let report = auth
+        .revoke_refresh_tokens("a".repeat(129))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        report.current_context(),
+        ApiClientError::Validation(ValidationError::InvalidUid)
+    ));
+
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_custom_tokens_need_signer() {
+    use super::token::CustomTokenError;
+    use crate::api_uri::ApiUriBuilder;
+
+    // Implementors without a signer only provide the client and URIs
+    struct UnsignedAuth(FirebaseAuth<ReqwestApiClient>);
+
+    impl FirebaseAuthService<ReqwestApiClient> for UnsignedAuth {
+        fn get_client(&self) -> &ReqwestApiClient {
+            &self.0.client
+        }
+
+        fn get_auth_uri_builder(&self) -> &ApiUriBuilder {
+            &self.0.auth_uri_builder
+        }
+    }
+
+    let auth = UnsignedAuth(App::emulated().auth("http://localhost:1".into()));
+    let result = auth.create_custom_token("user1".into(), None).await;
+
+    assert!(matches!(
+        result.unwrap_err().current_context(),
+        CustomTokenError::MissingSigner
+    ));
+}
@@ -0,0 +1,137 @@
+//! Custom token minting
+
+#[cfg(test)]
+mod test;
+
+use super::Claims;
+use crate::signer::Signer;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use error_stack::{Report, ResultExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use thiserror::Error;
+use time::{Duration, OffsetDateTime};
+
+const CUSTOM_TOKEN_AUDIENCE: &str =
+    "https://identitytoolkit.googleapis.com/google.identity.identitytoolkit.v1.IdentityToolkit";
+const CUSTOM_TOKEN_LIFETIME: Duration = Duration::hours(1);
+const MAX_UID_LENGTH: usize = 128;
+
+/// Claim names that can not be used as developer claims
+pub const RESERVED_CLAIMS: [&str; 15] = [
+    "acr",
+    "amr",
+    "at_hash",
+    "aud",
+    "auth_time",
+    "azp",
+    "cnf",
+    "c_hash",
+    "exp",
+    "firebase",
+    "iat",
+    "iss",
+    "jti",
+    "nbf",
+    "nonce",
+];
+
+#[derive(Error, Debug, Clone)]
+pub enum CustomTokenError {
+    #[error("User ID must be a non-empty string of at most 128 characters")]
+    InvalidUid,
+    #[error("Developer claim {0} is reserved")]
+    ReservedClaim(String),
+    #[error("No signer is available for minting custom tokens")]
+    MissingSigner,
+    #[error("Failed to sign custom token")]
+    FailedToSign,
+}
+
+#[derive(Serialize)]
+struct CustomTokenHeader<'a> {
+    alg: &'a str,
+    typ: &'a str,
+}
+
+#[derive(Serialize)]
+struct CustomTokenPayload<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+    uid: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tenant_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    claims: Option<&'a BTreeMap<String, Value>>,
+}
+
+fn validate_custom_token_input(
+    uid: &str,
+    developer_claims: Option<&Claims>,
+) -> Result<(), Report<CustomTokenError>> {
+    if uid.is_empty() || uid.chars().count() > MAX_UID_LENGTH {
+        return Err(Report::new(CustomTokenError::InvalidUid));
+    }
+
+    if let Some(claims) = developer_claims
+        && let Some(reserved) = claims
+            .get()
+            .keys()
+            .find(|k| RESERVED_CLAIMS.contains(&k.as_str()))
+    {
+        return Err(Report::new(CustomTokenError::ReservedClaim(
+            reserved.clone(),
+        )));
+    }
+
+    Ok(())
+}
+
+fn encode_segment<T: Serialize>(segment: &T) -> Result<String, Report<CustomTokenError>> {
+    let json = serde_json::to_vec(segment).change_context(CustomTokenError::FailedToSign)?;
+
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+/// Mint a custom token for given user signed by `signer`
+pub(crate) async fn encode_custom_token<S: Signer>(
+    signer: &S,
+    uid: &str,
+    developer_claims: Option<&Claims>,
+    tenant_id: Option<&str>,
+) -> Result<String, Report<CustomTokenError>> {
+    validate_custom_token_input(uid, developer_claims)?;
+
+    let account_id = signer
+        .account_id()
+        .await
+        .change_context(CustomTokenError::FailedToSign)?;
+    let issued_at = OffsetDateTime::now_utc();
+
+    let header = CustomTokenHeader {
+        alg: signer.algorithm(),
+        typ: "JWT",
+    };
+    let payload = CustomTokenPayload {
+        iss: &account_id,
+        sub: &account_id,
+        aud: CUSTOM_TOKEN_AUDIENCE,
+        iat: issued_at.unix_timestamp(),
+        exp: (issued_at + CUSTOM_TOKEN_LIFETIME).unix_timestamp(),
+        uid,
+        tenant_id,
+        claims: developer_claims.map(|c| c.get()).filter(|c| !c.is_empty()),
+    };
+
+    let signing_input = encode_segment(&header)? + "." + &encode_segment(&payload)?;
+    let signature = signer
+        .sign(signing_input.as_bytes())
+        .await
+        .change_context(CustomTokenError::FailedToSign)?;
+
+    Ok(signing_input + "." + &URL_SAFE_NO_PAD.encode(signature))
+}
@@ -0,0 +1,119 @@
+use super::{CUSTOM_TOKEN_AUDIENCE, CustomTokenError, encode_custom_token};
+use crate::auth::Claims;
+use crate::signer::{EmulatorSigner, ServiceAccountSigner};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rsa::RsaPrivateKey;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::sha2::Sha256;
+use rsa::signature::Verifier;
+use serde_json::{Value, from_slice, from_str};
+
+const TEST_SERVICE_ACCOUNT: &str = include_str!("../../signer/test_service_account.json");
+
+fn decode_segment(segment: &str) -> Value {
+    from_slice(&URL_SAFE_NO_PAD.decode(segment).unwrap()).unwrap()
+}
+
+fn test_claims() -> Claims {
+    let mut claims = Claims::default();
+    claims.get_mut().insert("premium".into(), Value::Bool(true));
+
+    claims
+}
+
+#[tokio::test]
+async fn test_emulator_custom_token() {
+    let token = encode_custom_token(&EmulatorSigner, "user1", Some(&test_claims()), None)
+        .await
+        .unwrap();
+
+    let segments: Vec<&str> = token.split('.').collect();
+    assert_eq!(segments.len(), 3);
+    assert!(segments[2].is_empty(), "Emulator tokens should be unsigned");
+
+    let header = decode_segment(segments[0]);
+    assert_eq!(header["alg"], "none");
+
+    let payload = decode_segment(segments[1]);
+    assert_eq!(payload["uid"], "user1");
+    assert_eq!(payload["aud"], CUSTOM_TOKEN_AUDIENCE);
+    assert_eq!(payload["iss"], "firebase-auth-emulator@example.com");
+    assert_eq!(payload["claims"]["premium"], true);
+    assert!(payload.get("tenant_id").is_none());
+    assert_eq!(
+        payload["exp"].as_i64().unwrap() - payload["iat"].as_i64().unwrap(),
+        3600
+    );
+}
+
+#[tokio::test]
+async fn test_tenant_custom_token() {
+    let token = encode_custom_token(&EmulatorSigner, "user1", None, Some("tenant-1"))
+        .await
+        .unwrap();
+
+    let payload = decode_segment(token.split('.').nth(1).unwrap());
+    assert_eq!(payload["tenant_id"], "tenant-1");
+}
+
+#[tokio::test]
+async fn test_service_account_custom_token() {
+    let signer = ServiceAccountSigner::from_json(TEST_SERVICE_ACCOUNT).unwrap();
+    let token = encode_custom_token(&signer, "user1", None, None)
+        .await
+        .unwrap();
+
+    let (signing_input, signature) = token.rsplit_once('.').unwrap();
+    let header = decode_segment(signing_input.split('.').next().unwrap());
+    assert_eq!(header["alg"], "RS256");
+
+    let payload = decode_segment(signing_input.split('.').nth(1).unwrap());
+    assert_eq!(
+        payload["sub"],
+        "firebase-adminsdk@demo-firebase-project.iam.gserviceaccount.com"
+    );
+    assert!(payload.get("claims").is_none());
+
+    let service_account: Value = from_str(TEST_SERVICE_ACCOUNT).unwrap();
+    let key =
+        RsaPrivateKey::from_pkcs8_pem(service_account["private_key"].as_str().unwrap()).unwrap();
+    let verifying_key = VerifyingKey::<Sha256>::new(key.to_public_key());
+    let signature =
+        Signature::try_from(URL_SAFE_NO_PAD.decode(signature).unwrap().as_slice()).unwrap();
+
+    verifying_key
+        .verify(signing_input.as_bytes(), &signature)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_custom_token_rejects_reserved_claims() {
+    let mut claims = Claims::default();
+    claims
+        .get_mut()
+        .insert("firebase".into(), Value::Bool(true));
+
+    let err = encode_custom_token(&EmulatorSigner, "user1", Some(&claims), None)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        CustomTokenError::ReservedClaim(claim) if claim == "firebase"
+    ));
+}
+
+#[tokio::test]
+async fn test_custom_token_rejects_invalid_uid() {
+    for uid in [String::new(), "a".repeat(129)] {
+        let err = encode_custom_token(&EmulatorSigner, &uid, None, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.current_context(),
+            CustomTokenError::InvalidUid
+        ));
+    }
+}
@@ -1,4 +1,6 @@
+use crate::validation::ValidationError;
 use serde::Deserialize;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Clone, Debug, Deserialize)]
@@ -12,6 +14,7 @@ pub struct FireBaseAPIErrorDetail {
 pub struct FireBaseAPIError {
     pub code: u16,
     pub message: String,
+    #[serde(default)]
     pub errors: Vec<FireBaseAPIErrorDetail>,
 }
 
@@ -31,6 +34,157 @@ pub enum ApiClientError {
     FailedToReceiveResponse,
     #[error("Failed to deserialize API response")]
     FailedToDeserializeResponse,
+    #[error("Update sets no fields")]
+    EmptyUpdate,
+    #[error("Invalid user import options")]
+    InvalidImportOptions,
+    #[error("Invalid user attribute: {0}")]
+    Validation(ValidationError),
     #[error("Server responded with an error {0:?}")]
     ServerError(FireBaseAPIError),
 }
+
+macro_rules! auth_error_codes {
+    ($($(#[$doc:meta])* $variant:ident => $code:literal,)+) => {
+        /// [Identity Toolkit error code](https://firebase.google.com/docs/reference/rest/auth#section-error-format)
+        /// carried at the start of an error message
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum AuthErrorCode {
+            $($(#[$doc])* $variant,)+
+            /// Code not known to this crate
+            Unknown(String),
+        }
+
+        impl AuthErrorCode {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $code,)+
+                    Self::Unknown(code) => code,
+                }
+            }
+        }
+
+        impl From<&str> for AuthErrorCode {
+            fn from(code: &str) -> Self {
+                match code {
+                    $($code => Self::$variant,)+
+                    code => Self::Unknown(code.into()),
+                }
+            }
+        }
+    };
+}
+
+auth_error_codes! {
+    ClaimsTooLarge => "CLAIMS_TOO_LARGE",
+    ConfigurationNotFound => "CONFIGURATION_NOT_FOUND",
+    CredentialMismatch => "CREDENTIAL_MISMATCH",
+    /// User ID already in use
+    DuplicateLocalId => "DUPLICATE_LOCAL_ID",
+    DuplicateEmail => "DUPLICATE_EMAIL",
+    DuplicateRawId => "DUPLICATE_RAW_ID",
+    EmailExists => "EMAIL_EXISTS",
+    EmailNotFound => "EMAIL_NOT_FOUND",
+    ExpiredOobCode => "EXPIRED_OOB_CODE",
+    ForbiddenClaim => "FORBIDDEN_CLAIM",
+    InsufficientPermission => "INSUFFICIENT_PERMISSION",
+    InvalidClaims => "INVALID_CLAIMS",
+    InvalidContinueUri => "INVALID_CONTINUE_URI",
+    InvalidCustomToken => "INVALID_CUSTOM_TOKEN",
+    InvalidDisplayName => "INVALID_DISPLAY_NAME",
+    InvalidDynamicLinkDomain => "INVALID_DYNAMIC_LINK_DOMAIN",
+    InvalidEmail => "INVALID_EMAIL",
+    InvalidIdToken => "INVALID_ID_TOKEN",
+    InvalidIdpResponse => "INVALID_IDP_RESPONSE",
+    InvalidOobCode => "INVALID_OOB_CODE",
+    InvalidPageSelection => "INVALID_PAGE_SELECTION",
+    InvalidPassword => "INVALID_PASSWORD",
+    InvalidPhoneNumber => "INVALID_PHONE_NUMBER",
+    InvalidPhotoUrl => "INVALID_PHOTO_URL",
+    InvalidProviderId => "INVALID_PROVIDER_ID",
+    InvalidSessionCookieDuration => "INVALID_SESSION_COOKIE_DURATION",
+    InvalidTenantId => "INVALID_TENANT_ID",
+    InvalidTenantType => "INVALID_TENANT_TYPE",
+    MaximumUserCountExceeded => "MAXIMUM_USER_COUNT_EXCEEDED",
+    MismatchingTenantId => "MISMATCHING_TENANT_ID",
+    MissingDisplayName => "MISSING_DISPLAY_NAME",
+    MissingEmail => "MISSING_EMAIL",
+    MissingLocalId => "MISSING_LOCAL_ID",
+    MissingOobCode => "MISSING_OOB_CODE",
+    MissingPassword => "MISSING_PASSWORD",
+    MissingRequestType => "MISSING_REQUEST_TYPE",
+    /// Batch deletion of an enabled user without `force`
+    NotDisabled => "NOT_DISABLED",
+    OperationNotAllowed => "OPERATION_NOT_ALLOWED",
+    PermissionDenied => "PERMISSION_DENIED",
+    PhoneNumberExists => "PHONE_NUMBER_EXISTS",
+    ProjectNotFound => "PROJECT_NOT_FOUND",
+    QuotaExceeded => "QUOTA_EXCEEDED",
+    SecondFactorLimitExceeded => "SECOND_FACTOR_LIMIT_EXCEEDED",
+    TenantNotFound => "TENANT_NOT_FOUND",
+    TokenExpired => "TOKEN_EXPIRED",
+    TooManyAttemptsTryLater => "TOO_MANY_ATTEMPTS_TRY_LATER",
+    UnauthorizedDomain => "UNAUTHORIZED_DOMAIN",
+    UnsupportedFirstFactor => "UNSUPPORTED_FIRST_FACTOR",
+    UnverifiedEmail => "UNVERIFIED_EMAIL",
+    UserDisabled => "USER_DISABLED",
+    UserNotFound => "USER_NOT_FOUND",
+    WeakPassword => "WEAK_PASSWORD",
+}
+
+impl fmt::Display for AuthErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FireBaseAPIError {
+    /// Error code from the message, e.g. `WEAK_PASSWORD` of
+    /// `WEAK_PASSWORD : Password should be at least 6 characters`
+    pub fn error_code(&self) -> AuthErrorCode {
+        split_error_message(&self.message).0.into()
+    }
+
+    /// Human readable detail following the error code, if any
+    pub fn detail(&self) -> Option<&str> {
+        split_error_message(&self.message).1
+    }
+}
+
+/// Split `CODE : detail` messages into their parts
+pub(crate) fn split_error_message(message: &str) -> (&str, Option<&str>) {
+    match message.split_once(':') {
+        Some((code, detail)) => (code.trim(), Some(detail.trim())),
+        None => (message.trim(), None),
+    }
+}
+
+impl ApiClientError {
+    /// Error code of a server error, `None` for errors raised by the client
+    pub fn auth_error_code(&self) -> Option<AuthErrorCode> {
+        match self {
+            Self::ServerError(error) => Some(error.error_code()),
+            _ => None,
+        }
+    }
+
+    pub fn is_user_not_found(&self) -> bool {
+        self.auth_error_code() == Some(AuthErrorCode::UserNotFound)
+    }
+
+    /// Email already in use, reported as either `EMAIL_EXISTS` or `DUPLICATE_EMAIL` depending on the endpoint
+    pub fn is_email_exists(&self) -> bool {
+        matches!(
+            self.auth_error_code(),
+            Some(AuthErrorCode::EmailExists | AuthErrorCode::DuplicateEmail)
+        )
+    }
+
+    pub fn is_phone_number_exists(&self) -> bool {
+        self.auth_error_code() == Some(AuthErrorCode::PhoneNumberExists)
+    }
+
+    pub fn is_uid_exists(&self) -> bool {
+        self.auth_error_code() == Some(AuthErrorCode::DuplicateLocalId)
+    }
+}
@@ -1,5 +1,8 @@
 //! HTTP(S) client traits for hanling API calls
 
+#[cfg(test)]
+mod test;
+
 pub mod error;
 pub mod url_params;
 
@@ -0,0 +1,64 @@
+use super::error::{ApiClientError, AuthErrorCode, FireBaseAPIError};
+use crate::App;
+use crate::auth::{FirebaseAuthService, NewUser};
+use crate::util::test_server::{StubResponse, TestServer};
+use serde_json::json;
+
+fn api_error(message: &str) -> FireBaseAPIError {
+    FireBaseAPIError {
+        code: 400,
+        message: message.into(),
+        errors: Vec::new(),
+    }
+}
+
+#[test]
+fn test_auth_error_code_parsing() {
+    let error = api_error("EMAIL_EXISTS");
+    assert_eq!(error.error_code(), AuthErrorCode::EmailExists);
+    assert_eq!(error.detail(), None);
+
+    let error = api_error("WEAK_PASSWORD : Password should be at least 6 characters");
+    assert_eq!(error.error_code(), AuthErrorCode::WeakPassword);
+    assert_eq!(
+        error.detail(),
+        Some("Password should be at least 6 characters")
+    );
+
+    let error = api_error("SOMETHING_NEW : Details");
+    assert_eq!(
+        error.error_code(),
+        AuthErrorCode::Unknown("SOMETHING_NEW".into())
+    );
+    assert_eq!(error.error_code().to_string(), "SOMETHING_NEW");
+
+    let error = ApiClientError::ServerError(api_error("USER_NOT_FOUND"));
+    assert!(error.is_user_not_found());
+    assert!(!error.is_email_exists());
+    assert_eq!(ApiClientError::FailedToSendRequest.auth_error_code(), None);
+
+    assert!(ApiClientError::ServerError(api_error("EMAIL_EXISTS")).is_email_exists());
+    assert!(ApiClientError::ServerError(api_error("DUPLICATE_EMAIL")).is_email_exists());
+}
+
+#[tokio::test]
+async fn test_server_error_code() {
+    let server = TestServer::start(|_| StubResponse {
+        status: 400,
+        ..StubResponse::json(
+            json!({"error": {"code": 400, "message": "EMAIL_EXISTS", "errors": []}}).to_string(),
+        )
+    })
+    .await;
+    let auth = App::emulated().auth(server.url.clone());
+
+    let error = auth
+        .create_user(NewUser::email_and_password(
+            "user1@example.com".into(),
+            "123456".into(),
+        ))
+        .await
+        .unwrap_err();
+
+    assert!(error.current_context().is_email_exists());
+}
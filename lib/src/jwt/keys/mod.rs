@@ -0,0 +1,182 @@
+//! Cache of public keys used for verifying token signatures
+
+#[cfg(test)]
+mod test;
+
+use error_stack::{Report, ResultExt};
+use headers::{CacheControl, HeaderMapExt};
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::jwk::JwkSet;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+
+/// Pause between refresh attempts while stale keys are served
+const FAILED_REFRESH_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug, Clone)]
+#[error("Failed to fetch public keys")]
+pub struct KeyFetchError;
+
+/// Format of the public key document
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum KeyFormat {
+    /// JSON Web Key Set
+    Jwks,
+    /// JSON map of key IDs to PEM encoded X.509 certificates
+    PemCertificates,
+}
+
+pub(crate) type PublicKeys = Arc<HashMap<String, DecodingKey>>;
+
+struct CachedKeys {
+    keys: PublicKeys,
+    expires: Instant,
+    retry_at: Option<Instant>,
+}
+
+impl CachedKeys {
+    fn is_usable(&self, now: Instant, stale_if_error: Duration) -> bool {
+        now < self.expires
+            || self
+                .retry_at
+                .is_some_and(|retry_at| now < retry_at && now < self.expires + stale_if_error)
+    }
+}
+
+pub(crate) struct KeyCacheSpec {
+    pub url: String,
+    pub format: KeyFormat,
+    pub refresh_interval: Duration,
+    pub honour_cache_control: bool,
+    pub stale_if_error: Duration,
+    pub fetch_timeout: Duration,
+}
+
+pub(crate) struct KeyCache {
+    client: reqwest::Client,
+    spec: KeyCacheSpec,
+    cached: RwLock<Option<CachedKeys>>,
+    refreshing: Mutex<()>,
+}
+
+impl KeyCache {
+    pub fn new(spec: KeyCacheSpec) -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: reqwest::Client::builder()
+                .timeout(spec.fetch_timeout)
+                .build()?,
+            spec,
+            cached: RwLock::new(None),
+            refreshing: Mutex::new(()),
+        })
+    }
+
+    async fn usable_keys(&self) -> Option<PublicKeys> {
+        self.cached
+            .read()
+            .await
+            .as_ref()
+            .filter(|c| c.is_usable(Instant::now(), self.spec.stale_if_error))
+            .map(|c| c.keys.clone())
+    }
+
+    /// Get current keys, fetching them if cache has expired.
+    /// Expired keys keep being served for `stale_if_error` while fetching fails.
+    pub async fn get(&self) -> Result<PublicKeys, Report<KeyFetchError>> {
+        if let Some(keys) = self.usable_keys().await {
+            return Ok(keys);
+        }
+
+        let _refreshing = self.refreshing.lock().await;
+        // keys might have been refreshed while waiting for the lock
+        if let Some(keys) = self.usable_keys().await {
+            return Ok(keys);
+        }
+
+        let result = self.fetch().await;
+        let mut cached = self.cached.write().await;
+        match result {
+            Ok(fetched) => {
+                let keys = fetched.keys.clone();
+                *cached = Some(fetched);
+
+                Ok(keys)
+            }
+            Err(err) => {
+                let now = Instant::now();
+                match cached.as_mut() {
+                    Some(stale) if now < stale.expires + self.spec.stale_if_error => {
+                        stale.retry_at = Some(now + FAILED_REFRESH_BACKOFF);
+
+                        Ok(stale.keys.clone())
+                    }
+                    _ => Err(err),
+                }
+            }
+        }
+    }
+
+    async fn fetch(&self) -> Result<CachedKeys, Report<KeyFetchError>> {
+        let resp = self
+            .client
+            .get(&self.spec.url)
+            .send()
+            .await
+            .change_context(KeyFetchError)?
+            .error_for_status()
+            .change_context(KeyFetchError)?;
+
+        let max_age = resp
+            .headers()
+            .typed_get::<CacheControl>()
+            .and_then(|cc| cc.max_age())
+            .filter(|_| self.spec.honour_cache_control);
+
+        let keys: HashMap<String, DecodingKey> = match self.spec.format {
+            KeyFormat::Jwks => resp
+                .json::<JwkSet>()
+                .await
+                .change_context(KeyFetchError)?
+                .keys
+                .iter()
+                .filter_map(|jwk| {
+                    Some((jwk.common.key_id.clone()?, DecodingKey::from_jwk(jwk).ok()?))
+                })
+                .collect(),
+            KeyFormat::PemCertificates => resp
+                .json::<HashMap<String, String>>()
+                .await
+                .change_context(KeyFetchError)?
+                .into_iter()
+                .filter_map(|(kid, pem)| {
+                    Some((kid, DecodingKey::from_rsa_pem(pem.as_bytes()).ok()?))
+                })
+                .collect(),
+        };
+
+        Ok(CachedKeys {
+            keys: Arc::new(keys),
+            expires: Instant::now() + max_age.unwrap_or(self.spec.refresh_interval),
+            retry_at: None,
+        })
+    }
+}
+
+/// Where validator gets its public keys from
+pub(crate) enum KeySource {
+    Cached(Box<KeyCache>),
+    #[cfg_attr(not(feature = "testing"), allow(dead_code))]
+    Static(PublicKeys),
+}
+
+impl KeySource {
+    pub async fn get(&self) -> Result<PublicKeys, Report<KeyFetchError>> {
+        match self {
+            Self::Cached(cache) => cache.get().await,
+            Self::Static(keys) => Ok(keys.clone()),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::{KeyCache, KeyCacheSpec, KeyFetchError, KeyFormat};
+use crate::jwt::test::test_jwks;
+use crate::util::test_server::{StubResponse, TestServer};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const TEST_PUBLIC_KEYS: &str = include_str!("test_public_keys.json");
+
+fn key_cache(server: &TestServer, format: KeyFormat) -> KeyCacheSpec {
+    KeyCacheSpec {
+        url: format!("{}/keys", server.url),
+        format,
+        refresh_interval: Duration::from_secs(3600),
+        honour_cache_control: true,
+        stale_if_error: Duration::ZERO,
+        fetch_timeout: Duration::from_secs(5),
+    }
+}
+
+fn with_cache_control(body: String, cache_control: &str) -> StubResponse {
+    let mut response = StubResponse::json(body);
+    response
+        .headers
+        .push(("cache-control".into(), cache_control.into()));
+
+    response
+}
+
+/// Serves keys on first request and fails every following one
+async fn flaky_server() -> TestServer {
+    let served = Arc::new(AtomicUsize::new(0));
+
+    TestServer::start(move |_| {
+        if served.fetch_add(1, Ordering::SeqCst) == 0 {
+            StubResponse::json(TEST_PUBLIC_KEYS.into())
+        } else {
+            StubResponse {
+                status: 500,
+                headers: Vec::new(),
+                body: String::new(),
+            }
+        }
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_jwks_keys_are_cached() {
+    let jwks = test_jwks();
+    let server = TestServer::start(move |_| StubResponse::json(jwks.clone())).await;
+    let cache = KeyCache::new(key_cache(&server, KeyFormat::Jwks)).unwrap();
+
+    let keys = cache.get().await.unwrap();
+    assert!(keys.contains_key("test-key-id"));
+    cache.get().await.unwrap();
+
+    assert_eq!(server.requests().len(), 1);
+    assert_eq!(server.requests()[0].path, "/keys");
+}
+
+#[tokio::test]
+async fn test_pem_certificate_keys() {
+    let server = TestServer::start(|_| StubResponse::json(TEST_PUBLIC_KEYS.into())).await;
+    let cache = KeyCache::new(key_cache(&server, KeyFormat::PemCertificates)).unwrap();
+
+    let keys = cache.get().await.unwrap();
+
+    assert!(keys.contains_key("test-key-id"));
+}
+
+#[tokio::test]
+async fn test_cache_control_max_age_honoured() {
+    let server =
+        TestServer::start(|_| with_cache_control(TEST_PUBLIC_KEYS.into(), "public, max-age=0"))
+            .await;
+    let cache = KeyCache::new(key_cache(&server, KeyFormat::PemCertificates)).unwrap();
+
+    cache.get().await.unwrap();
+    cache.get().await.unwrap();
+
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_cache_control_max_age_ignored() {
+    let server =
+        TestServer::start(|_| with_cache_control(TEST_PUBLIC_KEYS.into(), "public, max-age=0"))
+            .await;
+    let cache = KeyCache::new(KeyCacheSpec {
+        honour_cache_control: false,
+        ..key_cache(&server, KeyFormat::PemCertificates)
+    })
+    .unwrap();
+
+    cache.get().await.unwrap();
+    cache.get().await.unwrap();
+
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_stale_keys_served_while_refresh_fails() {
+    let server = flaky_server().await;
+    let cache = KeyCache::new(KeyCacheSpec {
+        refresh_interval: Duration::ZERO,
+        stale_if_error: Duration::from_secs(60),
+        ..key_cache(&server, KeyFormat::PemCertificates)
+    })
+    .unwrap();
+
+    cache.get().await.unwrap();
+    let keys = cache.get().await.unwrap();
+    assert!(keys.contains_key("test-key-id"));
+
+    // failed refresh is not retried right away
+    cache.get().await.unwrap();
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_expired_keys_not_served_without_stale_window() {
+    let server = flaky_server().await;
+    let cache = KeyCache::new(KeyCacheSpec {
+        refresh_interval: Duration::ZERO,
+        ..key_cache(&server, KeyFormat::PemCertificates)
+    })
+    .unwrap();
+
+    cache.get().await.unwrap();
+    let err = cache.get().await.unwrap_err();
+
+    assert!(matches!(err.current_context(), KeyFetchError));
+}
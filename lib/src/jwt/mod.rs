@@ -1,117 +1,518 @@
+mod keys;
+#[cfg(test)]
+mod test;
+
+use crate::auth::{Claims, FirebaseAuthService, UserIdentifiers};
+use crate::client::ApiHttpClient;
+use crate::validation::Uid;
 use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use core::future::Future;
 use error_stack::{Report, ResultExt};
-use jsonwebtoken::{DecodingKey, Validation, decode, decode_header};
-use jsonwebtoken_jwks_cache::{CachedJWKS, TimeoutSpec};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, Validation, decode, decode_header};
+use keys::{KeyCache, KeyCacheSpec, KeyFormat, KeySource};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
 use serde_json::{Value, from_slice};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::time::Duration;
 use thiserror::Error;
+use time::OffsetDateTime;
 
 const GOOGLE_JWKS_URI: &str =
     "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
 const GOOGLE_PKEYS_URI: &str =
     "https://www.googleapis.com/identitytoolkit/v3/relyingparty/publicKeys";
-const GOOGLE_ID_TOKEN_ISSUER_PREFIX: &str = "https://securetoken.google.com/";
-const GOOGLE_COOKIE_ISSUER_PREFIX: &str = "https://session.firebase.google.com/";
+pub(crate) const GOOGLE_ID_TOKEN_ISSUER_PREFIX: &str = "https://securetoken.google.com/";
+pub(crate) const GOOGLE_COOKIE_ISSUER_PREFIX: &str = "https://session.firebase.google.com/";
+const MAX_SUBJECT_LENGTH: usize = 128;
+const DEFAULT_KEYS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_KEYS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_LEEWAY: Duration = Duration::from_secs(60);
 
 #[derive(Error, Debug, Clone)]
 pub enum TokenVerificationError {
     #[error("Token's key is missing")]
     MissingKey,
+    #[error("Token is malformed")]
+    Malformed,
+    #[error("Token has expired")]
+    Expired,
+    #[error("Token is not yet valid")]
+    NotYetValid,
+    #[error("Token was issued for a different audience")]
+    WrongAudience,
+    #[error("Token was issued by an unexpected issuer")]
+    WrongIssuer,
+    #[error("Token's signature is invalid")]
+    InvalidSignature,
+    #[error("Token's algorithm is not supported")]
+    UnsupportedAlgorithm,
+    #[error("Token has no subject")]
+    MissingSubject,
+    #[error("Token's subject is longer than 128 characters")]
+    InvalidSubject,
     #[error("Invalid token")]
     Invalid,
     #[error("Unexpected error")]
     Internal,
+    #[error("Token has been revoked")]
+    Revoked,
+    #[error("User of the token is disabled")]
+    UserDisabled,
+    #[error("User of the token does not exist")]
+    UserNotFound,
+    #[error("Token was issued for a different tenant")]
+    WrongTenant,
+}
+
+impl From<&ErrorKind> for TokenVerificationError {
+    fn from(kind: &ErrorKind) -> Self {
+        match kind {
+            ErrorKind::ExpiredSignature => Self::Expired,
+            ErrorKind::ImmatureSignature => Self::NotYetValid,
+            ErrorKind::InvalidAudience => Self::WrongAudience,
+            ErrorKind::InvalidIssuer => Self::WrongIssuer,
+            ErrorKind::InvalidSignature => Self::InvalidSignature,
+            ErrorKind::InvalidAlgorithm
+            | ErrorKind::MissingAlgorithm
+            | ErrorKind::InvalidAlgorithmName => Self::UnsupportedAlgorithm,
+            ErrorKind::InvalidSubject => Self::InvalidSubject,
+            ErrorKind::MissingRequiredClaim(claim) if claim == "sub" => Self::MissingSubject,
+            ErrorKind::InvalidToken
+            | ErrorKind::MissingRequiredClaim(_)
+            | ErrorKind::InvalidClaimFormat(_)
+            | ErrorKind::Base64(_)
+            | ErrorKind::Json(_)
+            | ErrorKind::Utf8(_) => Self::Malformed,
+            ErrorKind::InvalidEcdsaKey
+            | ErrorKind::InvalidEddsaKey
+            | ErrorKind::InvalidRsaKey(_)
+            | ErrorKind::InvalidKeyFormat
+            | ErrorKind::Provider(_) => Self::Internal,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+/// Checks Firebase specific claims not covered by generic JWT validation
+fn verify_firebase_claims(
+    claims: &Value,
+    leeway: u64,
+    tenant_id: Option<&str>,
+) -> Result<(), Report<TokenVerificationError>> {
+    let subject = claims
+        .get("sub")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if subject.is_empty() {
+        return Err(Report::new(TokenVerificationError::MissingSubject));
+    }
+    if subject.chars().count() > MAX_SUBJECT_LENGTH {
+        return Err(Report::new(TokenVerificationError::InvalidSubject));
+    }
+
+    let latest_allowed = OffsetDateTime::now_utc().unix_timestamp() + leeway as i64;
+    for claim in ["iat", "auth_time"] {
+        let timestamp = claims
+            .get(claim)
+            .and_then(Value::as_i64)
+            .ok_or(TokenVerificationError::Malformed)
+            .attach_with(|| format!("Claim {claim} is missing"))?;
+
+        if timestamp > latest_allowed {
+            return Err(Report::new(TokenVerificationError::NotYetValid)
+                .attach(format!("Claim {claim} is in the future")));
+        }
+    }
+
+    if let Some(tenant_id) = tenant_id {
+        let token_tenant = claims
+            .get("firebase")
+            .and_then(|firebase| firebase.get("tenant"))
+            .and_then(Value::as_str);
+        if token_tenant != Some(tenant_id) {
+            return Err(Report::new(TokenVerificationError::WrongTenant)
+                .attach(format!("Expected {tenant_id}, got {token_tenant:?}")));
+        }
+    }
+
+    Ok(())
+}
+
+fn verification_error(err: jsonwebtoken::errors::Error) -> Report<TokenVerificationError> {
+    let context = TokenVerificationError::from(err.kind());
+
+    Report::new(err).change_context(context)
+}
+
+/// `firebase` claim of ID tokens and session cookies
+#[derive(Deserialize, Debug, Clone)]
+pub struct FirebaseClaims {
+    /// Identifiers of the user per sign-in provider
+    #[serde(default)]
+    pub identities: BTreeMap<String, Vec<String>>,
+    pub sign_in_provider: String,
+    pub sign_in_second_factor: Option<String>,
+    pub second_factor_identifier: Option<String>,
+    pub tenant: Option<String>,
+}
+
+/// Verified claims of an ID token or session cookie
+#[derive(Deserialize, Debug, Clone)]
+pub struct DecodedIdToken {
+    #[serde(rename = "sub")]
+    pub uid: String,
+    #[serde(rename = "iss")]
+    pub issuer: String,
+    #[serde(rename = "aud")]
+    pub audience: String,
+    #[serde(with = "time::serde::timestamp")]
+    pub auth_time: OffsetDateTime,
+    #[serde(rename = "iat", with = "time::serde::timestamp")]
+    pub issued_at: OffsetDateTime,
+    #[serde(rename = "exp", with = "time::serde::timestamp")]
+    pub expires_at: OffsetDateTime,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub phone_number: Option<String>,
+    pub picture: Option<String>,
+    pub firebase: FirebaseClaims,
+    /// All remaining claims, including custom claims of the user
+    #[serde(flatten, deserialize_with = "deserialize_remaining_claims")]
+    pub claims: Claims,
+}
+
+fn deserialize_remaining_claims<'de, D>(deserializer: D) -> Result<Claims, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, Value>::deserialize(deserializer).map(Claims::from)
 }
 
 pub trait TokenValidator {
-    /// Validate JWT returning all claims on success
+    /// Validate JWT deserializing its claims into `T`
+    /// # Example
+    /// ```rust
+    /// #[derive(Deserialize)]
+    /// struct MyClaims {
+    ///     sub: String,
+    ///     role: Option<String>,
+    /// }
+    ///
+    /// let claims: MyClaims = validator.validate_as(&token).await.unwrap();
+    /// ```
+    fn validate_as<T: DeserializeOwned + Send>(
+        &self,
+        token: &str,
+    ) -> impl Future<Output = Result<T, Report<TokenVerificationError>>> + Send;
+
+    /// Validate JWT returning its claims on success
     fn validate(
         &self,
         token: &str,
-    ) -> impl Future<Output = Result<HashMap<String, Value>, Report<TokenVerificationError>>> + Send + Sync;
+    ) -> impl Future<Output = Result<DecodedIdToken, Report<TokenVerificationError>>> + Send {
+        self.validate_as(token)
+    }
+
+    /// Validate JWT and look up its user, rejecting tokens issued before the user's refresh tokens
+    /// were revoked and tokens of disabled users
+    /// # Example
+    /// ```rust
+    /// let token = validator
+    ///     .validate_and_check_revoked(&token, &live_app.auth())
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    fn validate_and_check_revoked<A, C>(
+        &self,
+        token: &str,
+        auth: &A,
+    ) -> impl Future<Output = Result<DecodedIdToken, Report<TokenVerificationError>>> + Send
+    where
+        Self: Sync,
+        A: FirebaseAuthService<C>,
+        C: ApiHttpClient,
+    {
+        async move {
+            let token = self.validate(token).await?;
+            let uid =
+                Uid::new(token.uid.clone()).change_context(TokenVerificationError::UserNotFound)?;
+
+            let user = auth
+                .get_user(UserIdentifiers::builder().with_uid(uid).build())
+                .await
+                .change_context(TokenVerificationError::Internal)?
+                .ok_or(TokenVerificationError::UserNotFound)?;
+
+            if user.disabled.unwrap_or(false) {
+                return Err(Report::new(TokenVerificationError::UserDisabled));
+            }
+
+            if let Some(valid_since) = user.metadata.valid_since
+                && token.auth_time.unix_timestamp() < valid_since.unix_timestamp()
+            {
+                return Err(Report::new(TokenVerificationError::Revoked));
+            }
+
+            Ok(token)
+        }
+    }
 }
 
+/// Validates tokens signed by Google against periodically refreshed public keys
 pub struct LiveValidator {
     project_id: String,
     issuer: String,
-    jwks: CachedJWKS,
+    keys: KeySource,
+    leeway: Duration,
+    tenant_id: Option<String>,
 }
 
 impl LiveValidator {
     pub fn new_jwt_validator(project_id: String) -> Result<Self, reqwest::Error> {
-        Ok(Self {
-            issuer: format!("{GOOGLE_ID_TOKEN_ISSUER_PREFIX}{project_id}"),
-            project_id,
-            jwks: CachedJWKS::new(
-                // should always succeed
-                GOOGLE_JWKS_URI.parse().unwrap(),
-                Duration::from_secs(60),
-                TimeoutSpec::default(),
-            )?,
-        })
+        Self::jwt_validator_builder(project_id).build()
     }
 
     pub fn new_cookie_validator(project_id: String) -> Result<Self, reqwest::Error> {
-        Ok(Self {
-            issuer: format!("{GOOGLE_COOKIE_ISSUER_PREFIX}{project_id}"),
+        Self::cookie_validator_builder(project_id).build()
+    }
+
+    /// Validator trusting only given keys, never fetching any
+    #[cfg(feature = "testing")]
+    pub(crate) fn with_static_keys(
+        project_id: String,
+        issuer_prefix: &str,
+        keys: keys::PublicKeys,
+    ) -> Self {
+        Self {
+            issuer: format!("{issuer_prefix}{project_id}"),
+            project_id,
+            keys: KeySource::Static(keys),
+            leeway: DEFAULT_LEEWAY,
+            tenant_id: None,
+        }
+    }
+
+    /// Configure validator of ID tokens
+    pub fn jwt_validator_builder(project_id: String) -> LiveValidatorBuilder {
+        LiveValidatorBuilder::new(
+            project_id,
+            GOOGLE_ID_TOKEN_ISSUER_PREFIX,
+            GOOGLE_JWKS_URI,
+            KeyFormat::Jwks,
+        )
+    }
+
+    /// Configure validator of session cookies
+    pub fn cookie_validator_builder(project_id: String) -> LiveValidatorBuilder {
+        LiveValidatorBuilder::new(
+            project_id,
+            GOOGLE_COOKIE_ISSUER_PREFIX,
+            GOOGLE_PKEYS_URI,
+            KeyFormat::PemCertificates,
+        )
+    }
+}
+
+pub struct LiveValidatorBuilder {
+    project_id: String,
+    issuer: String,
+    keys: KeyCacheSpec,
+    leeway: Duration,
+    tenant_id: Option<String>,
+}
+
+impl LiveValidatorBuilder {
+    fn new(project_id: String, issuer_prefix: &str, keys_url: &str, format: KeyFormat) -> Self {
+        Self {
+            issuer: format!("{issuer_prefix}{project_id}"),
             project_id,
-            jwks: CachedJWKS::new_rsa_pkeys(
-                // should always succeed
-                GOOGLE_PKEYS_URI.parse().unwrap(),
-                Duration::from_secs(60),
-                TimeoutSpec::default(),
-            )?,
+            keys: KeyCacheSpec {
+                url: keys_url.into(),
+                format,
+                refresh_interval: DEFAULT_KEYS_REFRESH_INTERVAL,
+                honour_cache_control: true,
+                stale_if_error: Duration::ZERO,
+                fetch_timeout: DEFAULT_KEYS_FETCH_TIMEOUT,
+            },
+            leeway: DEFAULT_LEEWAY,
+            tenant_id: None,
+        }
+    }
+
+    /// Fetch public keys from given URL, in the format of the default endpoint
+    pub fn with_keys_url(mut self, url: String) -> Self {
+        self.keys.url = url;
+
+        self
+    }
+
+    /// How long fetched keys are cached, unless response `Cache-Control` says otherwise
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.keys.refresh_interval = interval;
+
+        self
+    }
+
+    /// Whether `max-age` of response `Cache-Control` overrides refresh interval
+    pub fn with_cache_control(mut self, honour: bool) -> Self {
+        self.keys.honour_cache_control = honour;
+
+        self
+    }
+
+    /// Tolerated clock skew when checking token timestamps
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+
+        self
+    }
+
+    /// Only accept tokens issued for given tenant
+    pub fn with_tenant(mut self, tenant_id: String) -> Self {
+        self.tenant_id = Some(tenant_id);
+
+        self
+    }
+
+    /// How long expired keys keep being used while refreshing them fails
+    pub fn with_stale_if_error(mut self, window: Duration) -> Self {
+        self.keys.stale_if_error = window;
+
+        self
+    }
+
+    /// Timeout of a single key fetch request
+    pub fn with_fetch_timeout(mut self, timeout: Duration) -> Self {
+        self.keys.fetch_timeout = timeout;
+
+        self
+    }
+
+    pub fn build(self) -> Result<LiveValidator, reqwest::Error> {
+        Ok(LiveValidator {
+            project_id: self.project_id,
+            issuer: self.issuer,
+            keys: KeySource::Cached(Box::new(KeyCache::new(self.keys)?)),
+            leeway: self.leeway,
+            tenant_id: self.tenant_id,
         })
     }
 }
 
 impl TokenValidator for LiveValidator {
-    async fn validate(
+    async fn validate_as<T: DeserializeOwned + Send>(
         &self,
         token: &str,
-    ) -> Result<HashMap<String, Value>, Report<TokenVerificationError>> {
-        let jwks = self
-            .jwks
+    ) -> Result<T, Report<TokenVerificationError>> {
+        let jwt_header = decode_header(token).map_err(verification_error)?;
+        if jwt_header.alg != Algorithm::RS256 {
+            return Err(Report::new(TokenVerificationError::UnsupportedAlgorithm)
+                .attach(format!("Expected RS256, got {:?}", jwt_header.alg)));
+        }
+
+        let keys = self
+            .keys
             .get()
             .await
             .change_context(TokenVerificationError::Internal)?;
-        let jwt_header = decode_header(token).change_context(TokenVerificationError::Invalid)?;
 
-        let jwk: DecodingKey = jwks
-            .find(&jwt_header.kid.ok_or(TokenVerificationError::MissingKey)?)
-            .ok_or(TokenVerificationError::MissingKey)?
-            .try_into()
-            .change_context(TokenVerificationError::Internal)?;
+        let key = keys
+            .get(&jwt_header.kid.ok_or(TokenVerificationError::MissingKey)?)
+            .ok_or(TokenVerificationError::MissingKey)?;
 
-        let mut validator = Validation::new(jwt_header.alg);
+        let mut validator = Validation::new(Algorithm::RS256);
         validator.set_audience(&[&self.project_id]);
         validator.set_issuer(&[&self.issuer]);
+        validator.set_required_spec_claims(&["exp", "aud", "iss", "sub"]);
+        validator.leeway = self.leeway.as_secs();
+
+        let claims = decode::<Value>(token, key, &validator)
+            .map_err(verification_error)?
+            .claims;
+        verify_firebase_claims(&claims, validator.leeway, self.tenant_id.as_deref())?;
 
-        decode::<HashMap<String, Value>>(token, &jwk, &validator)
-            .change_context(TokenVerificationError::Invalid)
-            .map(|t| t.claims)
+        T::deserialize(claims).change_context(TokenVerificationError::Malformed)
     }
 }
 
-#[derive(Default)]
-pub struct EmulatorValidator;
+/// Validates unsigned tokens issued by Firebase emulator, checking their claims only
+pub struct EmulatorValidator {
+    project_id: String,
+    issuer: String,
+    tenant_id: Option<String>,
+}
+
+impl EmulatorValidator {
+    pub fn new_jwt_validator(project_id: String) -> Self {
+        Self {
+            issuer: format!("{GOOGLE_ID_TOKEN_ISSUER_PREFIX}{project_id}"),
+            project_id,
+            tenant_id: None,
+        }
+    }
+
+    pub fn new_cookie_validator(project_id: String) -> Self {
+        Self {
+            issuer: format!("{GOOGLE_COOKIE_ISSUER_PREFIX}{project_id}"),
+            project_id,
+            tenant_id: None,
+        }
+    }
+
+    /// Only accept tokens issued for given tenant
+    pub fn with_tenant(mut self, tenant_id: String) -> Self {
+        self.tenant_id = Some(tenant_id);
+
+        self
+    }
+
+    fn verify_claims(&self, claims: &Value) -> Result<(), Report<TokenVerificationError>> {
+        let leeway = DEFAULT_LEEWAY.as_secs();
+        let expires_at = claims
+            .get("exp")
+            .and_then(Value::as_i64)
+            .ok_or(TokenVerificationError::Malformed)
+            .attach("Claim exp is missing")?;
+        if expires_at + (leeway as i64) < OffsetDateTime::now_utc().unix_timestamp() {
+            return Err(Report::new(TokenVerificationError::Expired));
+        }
+
+        let audience = claims.get("aud").and_then(Value::as_str);
+        if audience != Some(self.project_id.as_str()) {
+            return Err(Report::new(TokenVerificationError::WrongAudience)
+                .attach(format!("Expected {}, got {audience:?}", self.project_id)));
+        }
+
+        let issuer = claims.get("iss").and_then(Value::as_str);
+        if issuer != Some(self.issuer.as_str()) {
+            return Err(Report::new(TokenVerificationError::WrongIssuer)
+                .attach(format!("Expected {}, got {issuer:?}", self.issuer)));
+        }
+
+        verify_firebase_claims(claims, leeway, self.tenant_id.as_deref())
+    }
+}
 
 impl TokenValidator for EmulatorValidator {
-    async fn validate(
+    async fn validate_as<T: DeserializeOwned + Send>(
         &self,
         token: &str,
-    ) -> Result<HashMap<String, Value>, Report<TokenVerificationError>> {
-        let header = token
+    ) -> Result<T, Report<TokenVerificationError>> {
+        let payload = token
             .split(".")
             .nth(1)
-            .ok_or(TokenVerificationError::Invalid)?;
+            .ok_or(TokenVerificationError::Malformed)?;
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .change_context(TokenVerificationError::Malformed)?;
 
-        let header = URL_SAFE_NO_PAD
-            .decode(header)
-            .change_context(TokenVerificationError::Invalid)?;
+        let claims: Value =
+            from_slice(&payload).change_context(TokenVerificationError::Malformed)?;
+        self.verify_claims(&claims)?;
 
-        from_slice(&header).change_context(TokenVerificationError::Invalid)
+        T::deserialize(claims).change_context(TokenVerificationError::Malformed)
     }
 }
@@ -0,0 +1,504 @@
+use super::{
+    DecodedIdToken, EmulatorValidator, LiveValidator, TokenValidator, TokenVerificationError,
+};
+use crate::App;
+use crate::util::test_server::{StubResponse, TestServer};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use rsa::RsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::traits::PublicKeyParts;
+use serde::Deserialize;
+use serde_json::{Value, from_str, from_value, json};
+use time::OffsetDateTime;
+
+const TEST_SERVICE_ACCOUNT: &str = include_str!("../signer/test_service_account.json");
+const TEST_KEY_ID: &str = "test-key-id";
+const TEST_PROJECT_ID: &str = "demo-firebase-project";
+
+fn unsigned_token(claims: Value) -> String {
+    let header = URL_SAFE_NO_PAD.encode(json!({"alg": "none", "typ": "JWT"}).to_string());
+    let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+
+    format!("{header}.{payload}.")
+}
+
+fn id_token_claims(uid: &str, auth_time: i64) -> Value {
+    json!({
+        "iss": "https://securetoken.google.com/demo-firebase-project",
+        "aud": "demo-firebase-project",
+        "sub": uid,
+        "user_id": uid,
+        "auth_time": auth_time,
+        "iat": auth_time,
+        "exp": auth_time + 3600,
+        "email": "test@example.com",
+        "email_verified": false,
+        "role": "admin",
+        "firebase": {
+            "identities": {
+                "email": ["test@example.com"]
+            },
+            "sign_in_provider": "password",
+            "tenant": "tenant-1"
+        }
+    })
+}
+
+fn test_private_key_pem() -> String {
+    let service_account: Value = from_str(TEST_SERVICE_ACCOUNT).unwrap();
+
+    service_account["private_key"].as_str().unwrap().into()
+}
+
+pub(super) fn test_jwks() -> String {
+    let key = RsaPrivateKey::from_pkcs8_pem(&test_private_key_pem())
+        .unwrap()
+        .to_public_key();
+
+    json!({
+        "keys": [{
+            "kty": "RSA",
+            "alg": "RS256",
+            "use": "sig",
+            "kid": TEST_KEY_ID,
+            "n": URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+            "e": URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+        }]
+    })
+    .to_string()
+}
+
+fn signed_token(claims: &Value) -> String {
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(TEST_KEY_ID.into());
+    let key = EncodingKey::from_rsa_pem(test_private_key_pem().as_bytes()).unwrap();
+
+    encode(&header, claims, &key).unwrap()
+}
+
+fn fresh_id_token_claims(uid: &str) -> Value {
+    id_token_claims(uid, now() - 10)
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+fn emulator_validator() -> EmulatorValidator {
+    EmulatorValidator::new_jwt_validator(TEST_PROJECT_ID.into())
+}
+
+async fn live_validator() -> (TestServer, LiveValidator) {
+    let jwks = test_jwks();
+    let server = TestServer::start(move |_| StubResponse::json(jwks.clone())).await;
+
+    let validator = LiveValidator::jwt_validator_builder(TEST_PROJECT_ID.into())
+        .with_keys_url(format!("{}/jwks", server.url))
+        .build()
+        .unwrap();
+
+    (server, validator)
+}
+
+async fn assert_live_rejects(claims: Value, expected: TokenVerificationError) {
+    let (_server, validator) = live_validator().await;
+    let err = validator
+        .validate(&signed_token(&claims))
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        std::mem::discriminant(err.current_context()),
+        std::mem::discriminant(&expected),
+        "Expected {expected:?}, got {err:?}"
+    );
+}
+
+#[test]
+fn test_decoded_id_token() {
+    let decoded: DecodedIdToken = from_value(id_token_claims("user1", 1000)).unwrap();
+
+    assert_eq!(decoded.uid, "user1");
+    assert_eq!(decoded.audience, "demo-firebase-project");
+    assert_eq!(decoded.auth_time.unix_timestamp(), 1000);
+    assert_eq!(decoded.expires_at.unix_timestamp(), 4600);
+    assert_eq!(decoded.email.as_deref(), Some("test@example.com"));
+    assert_eq!(decoded.firebase.sign_in_provider, "password");
+    assert_eq!(
+        decoded.firebase.identities["email"],
+        vec!["test@example.com".to_string()]
+    );
+    assert_eq!(decoded.firebase.tenant.as_deref(), Some("tenant-1"));
+    assert_eq!(decoded.claims.get()["role"], "admin");
+    assert!(!decoded.claims.get().contains_key("sub"));
+}
+
+#[tokio::test]
+async fn test_validate_as() {
+    #[derive(Deserialize)]
+    struct RoleClaims {
+        sub: String,
+        role: String,
+    }
+
+    let token = unsigned_token(fresh_id_token_claims("user1"));
+    let claims: RoleClaims = emulator_validator().validate_as(&token).await.unwrap();
+
+    assert_eq!(claims.sub, "user1");
+    assert_eq!(claims.role, "admin");
+}
+
+#[tokio::test]
+async fn test_validate_and_check_revoked() {
+    let (server, auth) = TestServer::json_stub_client(
+        json!({"users": [json!({"localId": "user1", "validSince": (now() - 100).to_string()})]}),
+        |url| App::emulated().auth(url),
+    )
+    .await;
+    let token = unsigned_token(fresh_id_token_claims("user1"));
+
+    let decoded = emulator_validator()
+        .validate_and_check_revoked(&token, &auth)
+        .await
+        .unwrap();
+    assert_eq!(decoded.uid, "user1");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].path.ends_with("/accounts:lookup"));
+}
+
+#[tokio::test]
+async fn test_validate_and_check_revoked_rejects_revoked() {
+    let (_server, auth) = TestServer::json_stub_client(
+        json!({"users": [json!({"localId": "user1", "validSince": (now() - 100).to_string()})]}),
+        |url| App::emulated().auth(url),
+    )
+    .await;
+    let token = unsigned_token(id_token_claims("user1", now() - 1000));
+
+    let err = emulator_validator()
+        .validate_and_check_revoked(&token, &auth)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::Revoked
+    ));
+}
+
+#[tokio::test]
+async fn test_validate_and_check_revoked_rejects_disabled() {
+    let (_server, auth) = TestServer::json_stub_client(
+        json!({"users": [json!({"localId": "user1", "disabled": true})]}),
+        |url| App::emulated().auth(url),
+    )
+    .await;
+    let token = unsigned_token(fresh_id_token_claims("user1"));
+
+    let err = emulator_validator()
+        .validate_and_check_revoked(&token, &auth)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::UserDisabled
+    ));
+}
+
+#[test]
+fn test_verification_error_from_error_kind() {
+    let cases = [
+        (ErrorKind::ExpiredSignature, TokenVerificationError::Expired),
+        (
+            ErrorKind::ImmatureSignature,
+            TokenVerificationError::NotYetValid,
+        ),
+        (
+            ErrorKind::InvalidAudience,
+            TokenVerificationError::WrongAudience,
+        ),
+        (
+            ErrorKind::InvalidIssuer,
+            TokenVerificationError::WrongIssuer,
+        ),
+        (
+            ErrorKind::InvalidSignature,
+            TokenVerificationError::InvalidSignature,
+        ),
+        (
+            ErrorKind::InvalidAlgorithm,
+            TokenVerificationError::UnsupportedAlgorithm,
+        ),
+        (
+            ErrorKind::MissingRequiredClaim("sub".into()),
+            TokenVerificationError::MissingSubject,
+        ),
+        (
+            ErrorKind::MissingRequiredClaim("exp".into()),
+            TokenVerificationError::Malformed,
+        ),
+        (ErrorKind::InvalidToken, TokenVerificationError::Malformed),
+        (
+            ErrorKind::InvalidKeyFormat,
+            TokenVerificationError::Internal,
+        ),
+    ];
+
+    for (kind, expected) in cases {
+        let actual = TokenVerificationError::from(&kind);
+        assert_eq!(
+            std::mem::discriminant(&actual),
+            std::mem::discriminant(&expected),
+            "{kind:?} mapped to {actual:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_emulator_validator_rejects_malformed() {
+    let err = emulator_validator()
+        .validate("not-a-token")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::Malformed
+    ));
+}
+
+async fn assert_emulator_rejects(claims: Value, expected: TokenVerificationError) {
+    let err = emulator_validator()
+        .validate(&unsigned_token(claims))
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        std::mem::discriminant(err.current_context()),
+        std::mem::discriminant(&expected),
+        "Expected {expected:?}, got {err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_emulator_validator_rejects_expired() {
+    assert_emulator_rejects(
+        id_token_claims("user1", now() - 7200),
+        TokenVerificationError::Expired,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_emulator_validator_rejects_other_project() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims["aud"] = "other-project".into();
+    assert_emulator_rejects(claims, TokenVerificationError::WrongAudience).await;
+
+    let mut claims = fresh_id_token_claims("user1");
+    claims["iss"] = "https://securetoken.google.com/other-project".into();
+    assert_emulator_rejects(claims, TokenVerificationError::WrongIssuer).await;
+}
+
+#[tokio::test]
+async fn test_emulator_validator_rejects_missing_subject() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims.as_object_mut().unwrap().remove("sub");
+
+    assert_emulator_rejects(claims, TokenVerificationError::MissingSubject).await;
+}
+
+#[tokio::test]
+async fn test_validators_reject_missing_expiry_as_malformed() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims.as_object_mut().unwrap().remove("exp");
+
+    assert_emulator_rejects(claims.clone(), TokenVerificationError::Malformed).await;
+    assert_live_rejects(claims, TokenVerificationError::Malformed).await;
+}
+
+#[tokio::test]
+async fn test_emulator_cookie_validator() {
+    let mut claims = fresh_id_token_claims("user1");
+    let token = unsigned_token(claims.clone());
+    claims["iss"] = "https://session.firebase.google.com/demo-firebase-project".into();
+    let cookie = unsigned_token(claims);
+    let validator = EmulatorValidator::new_cookie_validator(TEST_PROJECT_ID.into());
+
+    assert_eq!(validator.validate(&cookie).await.unwrap().uid, "user1");
+    assert!(validator.validate(&token).await.is_err());
+}
+
+#[tokio::test]
+async fn test_live_validator() {
+    let (_server, validator) = live_validator().await;
+    let token = signed_token(&fresh_id_token_claims("user1"));
+
+    let decoded = validator.validate(&token).await.unwrap();
+    assert_eq!(decoded.uid, "user1");
+    assert_eq!(decoded.claims.get()["role"], "admin");
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_expired() {
+    assert_live_rejects(
+        id_token_claims("user1", 1000),
+        TokenVerificationError::Expired,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_wrong_audience() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims["aud"] = "other-project".into();
+
+    assert_live_rejects(claims, TokenVerificationError::WrongAudience).await;
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_wrong_issuer() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims["iss"] = "https://securetoken.google.com/other-project".into();
+
+    assert_live_rejects(claims, TokenVerificationError::WrongIssuer).await;
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_invalid_subject() {
+    assert_live_rejects(
+        fresh_id_token_claims(""),
+        TokenVerificationError::MissingSubject,
+    )
+    .await;
+    assert_live_rejects(
+        fresh_id_token_claims(&"a".repeat(129)),
+        TokenVerificationError::InvalidSubject,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_future_auth_time() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims["auth_time"] = (OffsetDateTime::now_utc().unix_timestamp() + 3600).into();
+
+    assert_live_rejects(claims, TokenVerificationError::NotYetValid).await;
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_future_iat() {
+    let mut claims = fresh_id_token_claims("user1");
+    claims["iat"] = (OffsetDateTime::now_utc().unix_timestamp() + 3600).into();
+
+    assert_live_rejects(claims, TokenVerificationError::NotYetValid).await;
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_tampered_signature() {
+    let (_server, validator) = live_validator().await;
+    let token = signed_token(&fresh_id_token_claims("user1"));
+
+    let (signing_input, _) = token.rsplit_once('.').unwrap();
+    let other_token = signed_token(&fresh_id_token_claims("user2"));
+    let (_, other_signature) = other_token.rsplit_once('.').unwrap();
+
+    let err = validator
+        .validate(&format!("{signing_input}.{other_signature}"))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::InvalidSignature
+    ));
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_other_algorithms() {
+    let (server, validator) = live_validator().await;
+
+    let mut header = Header::new(Algorithm::HS256);
+    header.kid = Some(TEST_KEY_ID.into());
+    let token = encode(
+        &header,
+        &fresh_id_token_claims("user1"),
+        &EncodingKey::from_secret(test_jwks().as_bytes()),
+    )
+    .unwrap();
+
+    let err = validator.validate(&token).await.unwrap_err();
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::UnsupportedAlgorithm
+    ));
+    assert!(
+        server.requests().is_empty(),
+        "Keys should not be fetched for unsupported algorithms"
+    );
+}
+
+#[tokio::test]
+async fn test_live_validator_rejects_unknown_key() {
+    let (_server, validator) = live_validator().await;
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("other-key-id".into());
+    let key = EncodingKey::from_rsa_pem(test_private_key_pem().as_bytes()).unwrap();
+    let token = encode(&header, &fresh_id_token_claims("user1"), &key).unwrap();
+
+    let err = validator.validate(&token).await.unwrap_err();
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::MissingKey
+    ));
+}
+
+#[tokio::test]
+async fn test_emulator_validator_checks_tenant() {
+    let token = unsigned_token(fresh_id_token_claims("user1"));
+
+    let decoded = emulator_validator()
+        .with_tenant("tenant-1".into())
+        .validate(&token)
+        .await
+        .unwrap();
+    assert_eq!(decoded.firebase.tenant.as_deref(), Some("tenant-1"));
+
+    let err = emulator_validator()
+        .with_tenant("tenant-2".into())
+        .validate(&token)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::WrongTenant
+    ));
+}
+
+#[tokio::test]
+async fn test_live_validator_checks_tenant() {
+    let jwks = test_jwks();
+    let server = TestServer::start(move |_| StubResponse::json(jwks.clone())).await;
+    let validator = LiveValidator::jwt_validator_builder(TEST_PROJECT_ID.into())
+        .with_keys_url(format!("{}/jwks", server.url))
+        .with_tenant("tenant-2".into())
+        .build()
+        .unwrap();
+
+    let mut claims = fresh_id_token_claims("user1");
+    claims["firebase"].as_object_mut().unwrap().remove("tenant");
+    let err = validator
+        .validate(&signed_token(&claims))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        TokenVerificationError::WrongTenant
+    ));
+}
@@ -4,7 +4,17 @@ pub mod client;
 pub mod credentials;
 #[cfg(feature = "tokens")]
 pub mod jwt;
+#[cfg(feature = "migration")]
+pub mod migration;
+#[cfg(feature = "password")]
+pub mod password;
+pub mod project_config;
+pub mod signer;
+pub mod tenant;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod util;
+pub mod validation;
 
 use auth::FirebaseAuth;
 use client::ReqwestApiClient;
@@ -13,6 +23,10 @@ use credentials::{GCPCredentialsError, emulator::EmulatorCredentials, get_projec
 use error_stack::{Report, ResultExt};
 use google_cloud_auth::credentials::{AccessTokenCredentials, Builder};
 pub use google_cloud_auth::credentials::{Credentials, CredentialsProvider};
+use project_config::ProjectConfigManager;
+use signer::{CredentialsSigner, GceMetadataClient, IamSigner, ServiceAccountSigner};
+use tenant::TenantManager;
+use validation::TenantId;
 
 const FIREBASE_AUTH_SCOPES: [&str; 2] = [
     "https://www.googleapis.com/auth/cloud-platform",
@@ -23,6 +37,7 @@ const FIREBASE_AUTH_SCOPES: [&str; 2] = [
 pub struct App<C> {
     credentials: Credentials,
     project_id: String,
+    service_account_signer: Option<ServiceAccountSigner>,
     _credentials_provider: PhantomData<C>,
 }
 
@@ -33,6 +48,7 @@ impl App<EmulatorCredentials> {
         Self {
             project_id: credentials.project_id.clone(),
             credentials: credentials.into(),
+            service_account_signer: None,
             _credentials_provider: PhantomData,
         }
     }
@@ -44,16 +60,49 @@ impl App<EmulatorCredentials> {
         FirebaseAuth::emulated(emulator_url, &self.project_id, client)
     }
 
-    /// OIDC token verifier for emulator
+    /// Firebase authentication manager for emulator scoped to a tenant
+    pub fn tenant_auth(
+        &self,
+        emulator_url: String,
+        tenant_id: &TenantId,
+    ) -> FirebaseAuth<ReqwestApiClient> {
+        self.auth(emulator_url).for_tenant(tenant_id)
+    }
+
+    /// Identity Platform tenant manager for emulator
+    pub fn tenant_manager(&self, emulator_url: String) -> TenantManager<ReqwestApiClient> {
+        let client = ReqwestApiClient::new(reqwest::Client::new(), self.credentials.clone());
+
+        TenantManager::emulated(emulator_url, &self.project_id, client)
+    }
+
+    /// Identity Platform project configuration manager for emulator
+    pub fn project_config_manager(
+        &self,
+        emulator_url: String,
+    ) -> ProjectConfigManager<ReqwestApiClient> {
+        let client = ReqwestApiClient::new(reqwest::Client::new(), self.credentials.clone());
+
+        ProjectConfigManager::emulated(emulator_url, &self.project_id, client)
+    }
+
+    /// OIDC token verifier for emulator, checks claims against the app's project ID
     #[cfg(feature = "tokens")]
     pub fn id_token_verifier(&self) -> impl jwt::TokenValidator {
-        jwt::EmulatorValidator
+        jwt::EmulatorValidator::new_jwt_validator(self.project_id.clone())
+    }
+
+    /// Cookie token verifier for emulator, checks claims against the app's project ID
+    #[cfg(feature = "tokens")]
+    pub fn cookie_token_verifier(&self) -> impl jwt::TokenValidator {
+        jwt::EmulatorValidator::new_cookie_validator(self.project_id.clone())
     }
 }
 
 impl App<AccessTokenCredentials> {
     /// Create instance of Firebase app for live project with an explicit project ID,
     /// bypassing environment variable and credential header resolution.
+    /// Fails when `GOOGLE_APPLICATION_CREDENTIALS` references an unreadable or malformed file.
     pub fn live_with_project_id(project_id: &str) -> Result<Self, Report<GCPCredentialsError>> {
         let credentials: Credentials = Builder::default()
             .with_scopes(FIREBASE_AUTH_SCOPES)
@@ -64,11 +113,14 @@ impl App<AccessTokenCredentials> {
         Ok(Self {
             credentials,
             project_id: project_id.to_string(),
+            service_account_signer: ServiceAccountSigner::from_env()
+                .change_context(GCPCredentialsError)?,
             _credentials_provider: PhantomData,
         })
     }
 
-    /// Create instance of Firebase app for live project
+    /// Create instance of Firebase app for live project.
+    /// Fails when `GOOGLE_APPLICATION_CREDENTIALS` references an unreadable or malformed file.
     pub async fn live() -> Result<Self, Report<GCPCredentialsError>> {
         let credentials: Credentials = Builder::default()
             .with_scopes(FIREBASE_AUTH_SCOPES)
@@ -83,15 +135,46 @@ impl App<AccessTokenCredentials> {
         Ok(Self {
             credentials,
             project_id,
+            service_account_signer: ServiceAccountSigner::from_env()
+                .change_context(GCPCredentialsError)?,
             _credentials_provider: PhantomData,
         })
     }
 
-    /// Create Firebase authentication manager
+    /// Create Firebase authentication manager,
+    /// custom tokens are signed with the service account key from `GOOGLE_APPLICATION_CREDENTIALS` if present,
+    /// otherwise through IAM `signBlob` API as the default service account of the compute instance
     pub fn auth(&self) -> FirebaseAuth<ReqwestApiClient> {
+        let http_client = reqwest::Client::new();
+        let client = ReqwestApiClient::new(http_client.clone(), self.credentials.clone());
+        let signer = match self.service_account_signer.clone() {
+            Some(signer) => CredentialsSigner::ServiceAccount(signer),
+            None => CredentialsSigner::Iam(IamSigner::from_metadata_server(
+                ReqwestApiClient::new(http_client.clone(), self.credentials.clone()),
+                GceMetadataClient::from_env(http_client),
+            )),
+        };
+
+        FirebaseAuth::live(&self.project_id, client).with_signer(signer)
+    }
+
+    /// Firebase authentication manager scoped to an Identity Platform tenant
+    pub fn tenant_auth(&self, tenant_id: &TenantId) -> FirebaseAuth<ReqwestApiClient> {
+        self.auth().for_tenant(tenant_id)
+    }
+
+    /// Identity Platform tenant manager
+    pub fn tenant_manager(&self) -> TenantManager<ReqwestApiClient> {
+        let client = ReqwestApiClient::new(reqwest::Client::new(), self.credentials.clone());
+
+        TenantManager::live(&self.project_id, client)
+    }
+
+    /// Identity Platform project configuration manager
+    pub fn project_config_manager(&self) -> ProjectConfigManager<ReqwestApiClient> {
         let client = ReqwestApiClient::new(reqwest::Client::new(), self.credentials.clone());
 
-        FirebaseAuth::live(&self.project_id, client)
+        ProjectConfigManager::live(&self.project_id, client)
     }
 
     /// Create OIDC token verifier
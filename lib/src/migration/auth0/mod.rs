@@ -0,0 +1,180 @@
+//! Auth0 bulk user exports and password hash exports, both newline delimited JSON
+//!
+//! Password hashes are bcrypt, import the records with `UserImportOptions::with_hash(PasswordHash::Bcrypt)`.
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    MigratedUser, MigrationError, MigrationLoss, bcrypt_hash, metadata_claims, read_ndjson,
+    validated,
+};
+use crate::auth::import::{UserImportRecord, UserProviderRecord};
+use crate::validation::{Email, PhoneNumber, PhotoUrl, Uid};
+use error_stack::Report;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::io::Read;
+use time::OffsetDateTime;
+
+/// Auth0 social connections and their Firebase provider IDs
+const PROVIDER_IDS: [(&str, &str); 6] = [
+    ("google-oauth2", "google.com"),
+    ("facebook", "facebook.com"),
+    ("github", "github.com"),
+    ("twitter", "twitter.com"),
+    ("apple", "apple.com"),
+    ("windowslive", "microsoft.com"),
+];
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Auth0ObjectId {
+    #[serde(rename = "$oid")]
+    pub oid: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Auth0Identity {
+    pub provider: String,
+    /// String or number depending on the provider
+    pub user_id: Value,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Auth0User {
+    /// User ID, e.g. `auth0|5f7c8ec7c33c6c004bbafe82`
+    pub user_id: Option<String>,
+    /// Database ID used by password hash exports instead of `user_id`
+    #[serde(rename = "_id")]
+    pub object_id: Option<Auth0ObjectId>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    #[serde(alias = "passwordHash")]
+    pub password_hash: Option<String>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub nickname: Option<String>,
+    pub picture: Option<String>,
+    pub phone_number: Option<String>,
+    pub blocked: Option<bool>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_login: Option<OffsetDateTime>,
+    pub identities: Option<Vec<Auth0Identity>>,
+    /// Mapped onto custom claims
+    pub app_metadata: Option<Map<String, Value>>,
+    pub user_metadata: Option<Map<String, Value>>,
+}
+
+impl From<Auth0User> for MigratedUser {
+    fn from(user: Auth0User) -> Self {
+        let mut losses = Vec::new();
+        let mut builder = UserImportRecord::builder();
+
+        if let Some(uid) = user
+            .user_id
+            .or_else(|| user.object_id.map(|id| format!("auth0|{}", id.oid)))
+            .and_then(|uid| validated("user_id", uid, Uid::new, &mut losses))
+        {
+            builder = builder.with_uid(uid);
+        }
+        if let Some(email) = user
+            .email
+            .and_then(|email| validated("email", email, Email::new, &mut losses))
+        {
+            builder = builder.with_email(email, user.email_verified.unwrap_or(false));
+        }
+        if let Some(hash) = user.password_hash {
+            match bcrypt_hash(&hash) {
+                Some(hash) => builder = builder.with_password_hash(hash, None),
+                None => losses.push(MigrationLoss::UnsupportedPasswordHash),
+            }
+        }
+        if let Some(name) = user.name {
+            builder = builder.with_display_name(name);
+        }
+        if let Some(picture) = user
+            .picture
+            .and_then(|picture| validated("picture", picture, PhotoUrl::new, &mut losses))
+        {
+            builder = builder.with_photo_url(picture);
+        }
+        if let Some(phone_number) = user.phone_number.and_then(|phone_number| {
+            validated("phone_number", phone_number, PhoneNumber::new, &mut losses)
+        }) {
+            builder = builder.with_phone_number(phone_number);
+        }
+        if user.blocked == Some(true) {
+            builder = builder.with_being_disabled();
+        }
+        if let Some(created_at) = user.created_at {
+            builder = builder.with_created_at(created_at);
+        }
+        if let Some(last_login) = user.last_login {
+            builder = builder.with_last_login_at(last_login);
+        }
+
+        for identity in user.identities.into_iter().flatten() {
+            let raw_id = match identity.user_id {
+                Value::String(id) => id,
+                id => id.to_string(),
+            };
+            // Database, passwordless email and SMS identities are carried by the user itself
+            if ["auth0", "email", "sms"].contains(&identity.provider.as_str()) {
+                continue;
+            }
+            match PROVIDER_IDS
+                .iter()
+                .find(|(provider, _)| *provider == identity.provider)
+            {
+                Some((_, provider_id)) => {
+                    builder = builder.with_provider(UserProviderRecord {
+                        provider_id: provider_id.to_string(),
+                        raw_id,
+                        ..Default::default()
+                    })
+                }
+                None => losses.push(MigrationLoss::DroppedIdentity {
+                    provider: identity.provider,
+                    user_id: Some(raw_id),
+                }),
+            }
+        }
+
+        if let Some(app_metadata) = user.app_metadata.filter(|m| !m.is_empty()) {
+            match metadata_claims(app_metadata) {
+                Some(claims) => builder = builder.with_custom_claims(claims),
+                None => losses.push(MigrationLoss::DroppedAttribute("app_metadata".into())),
+            }
+        }
+        let dropped = [
+            ("given_name", user.given_name.is_some()),
+            ("family_name", user.family_name.is_some()),
+            ("nickname", user.nickname.is_some()),
+            (
+                "user_metadata",
+                user.user_metadata.is_some_and(|m| !m.is_empty()),
+            ),
+        ];
+        losses.extend(
+            dropped
+                .into_iter()
+                .filter(|(_, present)| *present)
+                .map(|(name, _)| MigrationLoss::DroppedAttribute(name.into())),
+        );
+
+        Self {
+            record: builder.build(),
+            losses,
+        }
+    }
+}
+
+/// Stream users out of an Auth0 NDJSON export
+pub fn read_export<R: Read>(
+    reader: R,
+) -> impl Iterator<Item = Result<MigratedUser, Report<MigrationError>>> {
+    read_ndjson::<R, Auth0User>(reader)
+}
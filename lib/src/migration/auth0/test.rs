@@ -0,0 +1,59 @@
+use super::read_export;
+use crate::migration::{MigrationBatch, MigrationLoss};
+use crate::validation::ValidationError;
+use serde_json::to_value;
+
+const EXPORT: &str = r#"{"user_id":"auth0|5f7c8ec7c33c6c004bbafe82","email":"user1@example.com","email_verified":true,"name":"User One","created_at":"2021-03-05T16:45:56.695Z","identities":[{"provider":"auth0","user_id":"5f7c8ec7c33c6c004bbafe82"},{"provider":"github","user_id":1234}],"app_metadata":{"roles":["admin"]}}
+{"_id":{"$oid":"60425dc43519d90068f82973"},"email":"user2@example.com","email_verified":false,"passwordHash":"$2b$10$C9mAc0KF0zZ6QDLzPWDr4eCIwR9Bfj7xNfRXPJ9aWvJ4ldL4CmkSG","connection":"Username-Password-Authentication"}
+{"user_id":"oauth2|custom|42","given_name":"Three","identities":[{"provider":"oauth2","user_id":"custom|42"}],"user_metadata":{"plan":"pro"},"passwordHash":"sha1:deadbeef","phone_number":"555-1234"}
+"#;
+
+#[test]
+fn test_auth0_export_conversion() {
+    let batch: MigrationBatch = read_export(EXPORT.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(batch.records.len(), 3);
+
+    let first = to_value(&batch.records[0]).unwrap();
+    assert_eq!(first["localId"], "auth0|5f7c8ec7c33c6c004bbafe82");
+    assert_eq!(first["createdAt"], "1614962756695");
+    assert_eq!(first["customAttributes"], "{\"roles\":[\"admin\"]}");
+    assert_eq!(first["providerUserInfo"][0]["providerId"], "github.com");
+    assert_eq!(first["providerUserInfo"][0]["rawId"], "1234");
+
+    let second = to_value(&batch.records[1]).unwrap();
+    assert_eq!(second["localId"], "auth0|60425dc43519d90068f82973");
+    assert_eq!(
+        second["passwordHash"],
+        "JDJiJDEwJEM5bUFjMEtGMHpaNlFETHpQV0RyNGVDSXdSOUJmajd4TmZSWFBKOWFXdko0bGRMNENta1NH"
+    );
+
+    assert_eq!(batch.lossy.len(), 1);
+    assert_eq!(batch.lossy[0].index, 2);
+    assert_eq!(
+        batch.lossy[0].losses,
+        vec![
+            MigrationLoss::UnsupportedPasswordHash,
+            MigrationLoss::InvalidAttribute {
+                name: "phone_number".into(),
+                error: ValidationError::InvalidPhoneNumber
+            },
+            MigrationLoss::DroppedIdentity {
+                provider: "oauth2".into(),
+                user_id: Some("custom|42".into())
+            },
+            MigrationLoss::DroppedAttribute("given_name".into()),
+            MigrationLoss::DroppedAttribute("user_metadata".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_auth0_malformed_line() {
+    let mut users = read_export("{\"user_id\":\"a\"}\n{\"user_id\":".as_bytes());
+
+    assert!(users.next().unwrap().is_ok());
+    assert!(users.next().unwrap().is_err());
+}
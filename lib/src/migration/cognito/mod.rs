@@ -0,0 +1,126 @@
+//! Amazon Cognito user pool CSV exports, with the attribute names as header row
+//!
+//! Cognito does not export password hashes, migrated users have to reset their password
+//! or sign in with a linked provider.
+
+#[cfg(test)]
+mod test;
+
+use super::{MigratedUser, MigrationError, MigrationLoss, validated};
+use crate::auth::MultiFactorInfo;
+use crate::auth::import::UserImportRecord;
+use crate::validation::{Email, PhoneNumber, PhotoUrl, Uid};
+use error_stack::{Report, ResultExt};
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// Attributes mapped onto the record or without information worth keeping
+const MAPPED_ATTRIBUTES: [&str; 11] = [
+    "sub",
+    "cognito:username",
+    "email",
+    "email_verified",
+    "phone_number",
+    "phone_number_verified",
+    "name",
+    "picture",
+    "cognito:mfa_enabled",
+    "enabled",
+    "updated_at",
+];
+
+/// Non-empty attributes of an exported user by their column name
+#[derive(Debug, Clone, Default)]
+pub struct CognitoUser {
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl From<CognitoUser> for MigratedUser {
+    fn from(user: CognitoUser) -> Self {
+        let mut attributes = user.attributes;
+        let mut take = |name: &str| attributes.remove(name);
+        let mut losses = Vec::new();
+        let mut builder = UserImportRecord::builder();
+
+        // `sub` is immutable while the username may be reassigned
+        let sub = take("sub");
+        let username = take("cognito:username");
+        if let Some(uid) = sub
+            .or(username)
+            .and_then(|uid| validated("sub", uid, Uid::new, &mut losses))
+        {
+            builder = builder.with_uid(uid);
+        }
+        if let Some(email) =
+            take("email").and_then(|email| validated("email", email, Email::new, &mut losses))
+        {
+            builder = builder.with_email(email, take("email_verified").as_deref() == Some("true"));
+        }
+        let phone_number = take("phone_number").and_then(|phone_number| {
+            validated("phone_number", phone_number, PhoneNumber::new, &mut losses)
+        });
+        if let Some(phone_number) = &phone_number {
+            builder = builder.with_phone_number(phone_number.clone());
+        }
+        if let Some(name) = take("name") {
+            builder = builder.with_display_name(name);
+        }
+        if let Some(picture) = take("picture")
+            .and_then(|picture| validated("picture", picture, PhotoUrl::new, &mut losses))
+        {
+            builder = builder.with_photo_url(picture);
+        }
+        if take("enabled").as_deref() == Some("false") {
+            builder = builder.with_being_disabled();
+        }
+        if take("cognito:mfa_enabled").as_deref() == Some("true") {
+            match phone_number {
+                Some(phone_number) => {
+                    builder =
+                        builder.with_multi_factor(vec![MultiFactorInfo::phone(phone_number, None)])
+                }
+                None => losses.push(MigrationLoss::DroppedAttribute(
+                    "cognito:mfa_enabled".into(),
+                )),
+            }
+        }
+
+        losses.extend(
+            attributes
+                .into_keys()
+                .filter(|name| !MAPPED_ATTRIBUTES.contains(&name.as_str()))
+                .map(MigrationLoss::DroppedAttribute),
+        );
+
+        Self {
+            record: builder.build(),
+            losses,
+        }
+    }
+}
+
+/// Stream users out of a Cognito CSV export
+pub fn read_export<R: Read>(
+    reader: R,
+) -> Result<
+    impl Iterator<Item = Result<MigratedUser, Report<MigrationError>>>,
+    Report<MigrationError>,
+> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader
+        .headers()
+        .change_context(MigrationError::MalformedExport)?
+        .clone();
+
+    Ok(reader.into_records().map(move |record| {
+        let record = record.change_context(MigrationError::FailedToRead)?;
+        let attributes = headers
+            .iter()
+            .zip(record.iter())
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Ok(CognitoUser { attributes }.into())
+    }))
+}
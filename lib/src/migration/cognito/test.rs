@@ -0,0 +1,38 @@
+use super::read_export;
+use crate::migration::{MigrationBatch, MigrationLoss};
+use serde_json::to_value;
+
+const EXPORT: &str = "\
+cognito:username,sub,name,given_name,email,email_verified,phone_number,phone_number_verified,cognito:mfa_enabled,custom:tier,enabled
+user1,3f9c2d1e-5b4a-4c3d-9e8f-7a6b5c4d3e2f,User One,,user1@example.com,true,+15551234567,true,true,,true
+user2,,,Two,user2@example.com,false,,,true,gold,false
+";
+
+#[test]
+fn test_cognito_export_conversion() {
+    let batch: MigrationBatch = read_export(EXPORT.as_bytes())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let first = to_value(&batch.records[0]).unwrap();
+    assert_eq!(first["localId"], "3f9c2d1e-5b4a-4c3d-9e8f-7a6b5c4d3e2f");
+    assert_eq!(first["emailVerified"], true);
+    assert_eq!(first["displayName"], "User One");
+    assert_eq!(first["mfaInfo"][0]["phoneInfo"], "+15551234567");
+
+    let second = to_value(&batch.records[1]).unwrap();
+    assert_eq!(second["localId"], "user2");
+    assert_eq!(second["disabled"], true);
+
+    assert_eq!(batch.lossy.len(), 1);
+    assert_eq!(batch.lossy[0].uid.as_deref(), Some("user2"));
+    assert_eq!(
+        batch.lossy[0].losses,
+        vec![
+            MigrationLoss::DroppedAttribute("cognito:mfa_enabled".into()),
+            MigrationLoss::DroppedAttribute("custom:tier".into()),
+            MigrationLoss::DroppedAttribute("given_name".into()),
+        ]
+    );
+}
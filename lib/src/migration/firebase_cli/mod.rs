@@ -0,0 +1,488 @@
+//! Files produced by `firebase auth:export` and consumed by `firebase auth:import`
+
+#[cfg(test)]
+mod test;
+
+use super::MigrationError;
+use crate::auth::import::{UserImportRecord, UserProviderRecord};
+use crate::auth::{Claims, MultiFactorInfo, User};
+use crate::util::serialize::{deserialize_opt_str_epoch_ms, serialize_opt_str_epoch_ms};
+use crate::validation::{Email, PhoneNumber, PhotoUrl, Uid, ValidationError};
+use error_stack::{Report, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use time::OffsetDateTime;
+
+/// Providers with dedicated columns in CSV exports, in column order
+const CSV_PROVIDERS: [&str; 4] = ["google.com", "facebook.com", "twitter.com", "github.com"];
+/// Columns written by older CLI versions, without disabled flag and custom claims
+const MIN_CSV_COLUMNS: usize = 26;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedProviderInfo {
+    pub provider_id: String,
+    pub raw_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+}
+
+/// Single user of an export, password hash and salt are base64 encoded
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedUser {
+    #[serde(rename = "localId")]
+    pub uid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(default, rename = "lastSignedInAt")]
+    #[serde(deserialize_with = "deserialize_opt_str_epoch_ms")]
+    #[serde(serialize_with = "serialize_opt_str_epoch_ms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_login_at: Option<OffsetDateTime>,
+    #[serde(default, deserialize_with = "deserialize_opt_str_epoch_ms")]
+    #[serde(serialize_with = "serialize_opt_str_epoch_ms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(rename = "customAttributes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_claims: Option<Claims>,
+    #[serde(default)]
+    pub provider_user_info: Vec<ExportedProviderInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mfa_info: Vec<MultiFactorInfo>,
+}
+
+impl From<&User> for ExportedUser {
+    fn from(user: &User) -> Self {
+        Self {
+            uid: user.uid.clone(),
+            email: user.email.clone(),
+            email_verified: user.email_verified.unwrap_or(false),
+            password_hash: user.password_hash.clone(),
+            salt: user.salt.clone(),
+            display_name: user.display_name.clone(),
+            photo_url: user.photo_url.clone(),
+            phone_number: user.phone_number.clone(),
+            last_login_at: user.metadata.last_login_at,
+            created_at: user.metadata.created_at,
+            disabled: user.disabled.unwrap_or(false),
+            custom_claims: user.custom_claims.clone(),
+            provider_user_info: user
+                .provider_user_info
+                .iter()
+                .flatten()
+                .filter(|info| is_federated_provider(&info.provider_id))
+                .map(|info| ExportedProviderInfo {
+                    provider_id: info.provider_id.clone(),
+                    raw_id: info.raw_id.clone(),
+                    email: info.email.clone(),
+                    display_name: info.display_name.clone(),
+                    photo_url: info.photo_url.clone(),
+                })
+                .collect(),
+            mfa_info: user.mfa_info.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<ExportedUser> for UserImportRecord {
+    type Error = Report<MigrationError>;
+
+    fn try_from(user: ExportedUser) -> Result<Self, Self::Error> {
+        let invalid = |error: ValidationError| {
+            Report::new(error).change_context(MigrationError::InvalidRecord(user.uid.clone()))
+        };
+        let mut builder =
+            UserImportRecord::builder().with_uid(Uid::new(user.uid.clone()).map_err(invalid)?);
+
+        if let Some(email) = user.email {
+            builder = builder.with_email(Email::new(email).map_err(invalid)?, user.email_verified);
+        }
+        if let Some(hash) = user.password_hash {
+            builder = builder.with_password_hash(hash, user.salt);
+        }
+        if let Some(display_name) = user.display_name {
+            builder = builder.with_display_name(display_name);
+        }
+        if let Some(photo_url) = user.photo_url {
+            builder = builder.with_photo_url(PhotoUrl::new(photo_url).map_err(invalid)?);
+        }
+        if let Some(phone_number) = user.phone_number {
+            builder = builder.with_phone_number(PhoneNumber::new(phone_number).map_err(invalid)?);
+        }
+        if let Some(created_at) = user.created_at {
+            builder = builder.with_created_at(created_at);
+        }
+        if let Some(last_login_at) = user.last_login_at {
+            builder = builder.with_last_login_at(last_login_at);
+        }
+        if let Some(custom_claims) = user.custom_claims {
+            builder = builder.with_custom_claims(custom_claims);
+        }
+        if user.disabled {
+            builder = builder.with_being_disabled();
+        }
+        if !user.mfa_info.is_empty() {
+            builder = builder.with_multi_factor(user.mfa_info);
+        }
+        for info in user
+            .provider_user_info
+            .into_iter()
+            .filter(|info| is_federated_provider(&info.provider_id))
+        {
+            builder = builder.with_provider(UserProviderRecord {
+                provider_id: info.provider_id,
+                raw_id: info.raw_id,
+                email: info.email,
+                display_name: info.display_name,
+                photo_url: info.photo_url,
+            });
+        }
+
+        Ok(builder.build())
+    }
+}
+
+/// Password and phone sign in are covered by the top level fields,
+/// so they are not carried over as linked providers
+fn is_federated_provider(provider_id: &str) -> bool {
+    provider_id != "password" && provider_id != "phone"
+}
+
+/// Streams users out of a JSON export, `{"users": [...]}`, without loading the whole file
+pub struct JsonExportReader<R> {
+    reader: R,
+    started: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> JsonExportReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Consume bytes up to and including `until`, only whitespace is allowed on the way
+    fn skip_to(&mut self, until: u8) -> Result<(), Report<MigrationError>> {
+        loop {
+            let buf = self
+                .reader
+                .fill_buf()
+                .change_context(MigrationError::FailedToRead)?;
+            let Some(&byte) = buf.first() else {
+                return Err(
+                    Report::new(MigrationError::MalformedExport).attach("Unexpected end of export")
+                );
+            };
+            self.reader.consume(1);
+
+            if byte == until {
+                return Ok(());
+            }
+            if !byte.is_ascii_whitespace() {
+                return Err(Report::new(MigrationError::MalformedExport)
+                    .attach(format!("Unexpected character {:?}", byte as char)));
+            }
+        }
+    }
+
+    /// Next non-whitespace byte without consuming it
+    fn peek(&mut self) -> Result<Option<u8>, Report<MigrationError>> {
+        loop {
+            let buf = self
+                .reader
+                .fill_buf()
+                .change_context(MigrationError::FailedToRead)?;
+            match buf.first() {
+                Some(byte) if byte.is_ascii_whitespace() => self.reader.consume(1),
+                byte => return Ok(byte.copied()),
+            }
+        }
+    }
+
+    fn next_user(&mut self) -> Result<Option<ExportedUser>, Report<MigrationError>> {
+        if !self.started {
+            self.started = true;
+            self.skip_to(b'{')?;
+            let key =
+                String::deserialize(&mut serde_json::Deserializer::from_reader(&mut self.reader))
+                    .change_context(MigrationError::MalformedExport)?;
+            if key != "users" {
+                return Err(Report::new(MigrationError::MalformedExport)
+                    .attach(format!("Expected users, found {key}")));
+            }
+            self.skip_to(b':')?;
+            self.skip_to(b'[')?;
+            if self.peek()? == Some(b']') {
+                return Ok(None);
+            }
+        } else {
+            match self.peek()? {
+                Some(b']') => return Ok(None),
+                Some(b',') => self.reader.consume(1),
+                _ => {
+                    return Err(Report::new(MigrationError::MalformedExport)
+                        .attach("Expected , or ] after user"));
+                }
+            }
+        }
+
+        let user =
+            ExportedUser::deserialize(&mut serde_json::Deserializer::from_reader(&mut self.reader))
+                .change_context(MigrationError::MalformedExport)?;
+
+        Ok(Some(user))
+    }
+}
+
+impl<R: BufRead> Iterator for JsonExportReader<R> {
+    type Item = Result<ExportedUser, Report<MigrationError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let user = self.next_user().transpose();
+        if !matches!(user, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        user
+    }
+}
+
+/// Writes users into a JSON export one at a time, `finish` closes the document
+pub struct JsonExportWriter<W: Write> {
+    writer: W,
+    count: usize,
+}
+
+impl<W: Write> JsonExportWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    pub fn write_user(&mut self, user: &ExportedUser) -> Result<(), Report<MigrationError>> {
+        let separator = if self.count == 0 {
+            "{\"users\": [\n"
+        } else {
+            ",\n"
+        };
+        self.writer
+            .write_all(separator.as_bytes())
+            .change_context(MigrationError::FailedToWrite)?;
+        serde_json::to_writer(&mut self.writer, user)
+            .change_context(MigrationError::FailedToWrite)?;
+        self.count += 1;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, Report<MigrationError>> {
+        let closing = if self.count == 0 {
+            "{\"users\": [\n]}\n"
+        } else {
+            "\n]}\n"
+        };
+        self.writer
+            .write_all(closing.as_bytes())
+            .change_context(MigrationError::FailedToWrite)?;
+        self.writer
+            .flush()
+            .change_context(MigrationError::FailedToWrite)?;
+
+        Ok(self.writer)
+    }
+}
+
+fn csv_field(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn csv_timestamp(value: &str, uid: &str) -> Result<Option<OffsetDateTime>, Report<MigrationError>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let unix_ts_ms: i128 = value
+        .parse()
+        .change_context_lazy(|| MigrationError::InvalidRecord(uid.to_string()))?;
+
+    OffsetDateTime::from_unix_timestamp_nanos(unix_ts_ms * 1000000)
+        .map(Some)
+        .change_context_lazy(|| MigrationError::InvalidRecord(uid.to_string()))
+}
+
+fn csv_timestamp_string(value: Option<OffsetDateTime>) -> String {
+    value
+        .map(|dt| (dt.unix_timestamp_nanos() / 1000000).to_string())
+        .unwrap_or_default()
+}
+
+/// Streams users out of a CSV export, which has no header row
+pub struct CsvExportReader<R> {
+    records: csv::StringRecordsIntoIter<R>,
+}
+
+impl<R: std::io::Read> CsvExportReader<R> {
+    pub fn new(reader: R) -> Self {
+        let records = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(reader)
+            .into_records();
+
+        Self { records }
+    }
+}
+
+fn parse_csv_user(record: &csv::StringRecord) -> Result<ExportedUser, Report<MigrationError>> {
+    if record.len() < MIN_CSV_COLUMNS {
+        return Err(Report::new(MigrationError::MalformedExport).attach(format!(
+            "Expected at least {MIN_CSV_COLUMNS} columns, found {}",
+            record.len()
+        )));
+    }
+    let column = |index: usize| record.get(index).unwrap_or_default();
+    let uid = column(0).to_string();
+    let provider_columns = 7 + CSV_PROVIDERS.len() * 4;
+
+    let custom_claims = match column(provider_columns + 4) {
+        "" => None,
+        claims => Some(
+            serde_json::from_value::<Claims>(claims.into())
+                .change_context_lazy(|| MigrationError::InvalidRecord(uid.clone()))?,
+        ),
+    };
+
+    Ok(ExportedUser {
+        email: csv_field(column(1)),
+        email_verified: column(2) == "true",
+        password_hash: csv_field(column(3)),
+        salt: csv_field(column(4)),
+        display_name: csv_field(column(5)),
+        photo_url: csv_field(column(6)),
+        provider_user_info: CSV_PROVIDERS
+            .iter()
+            .enumerate()
+            .filter_map(|(i, provider_id)| {
+                let offset = 7 + i * 4;
+                csv_field(column(offset)).map(|raw_id| ExportedProviderInfo {
+                    provider_id: provider_id.to_string(),
+                    raw_id,
+                    email: csv_field(column(offset + 1)),
+                    display_name: csv_field(column(offset + 2)),
+                    photo_url: csv_field(column(offset + 3)),
+                })
+            })
+            .collect(),
+        created_at: csv_timestamp(column(provider_columns), &uid)?,
+        last_login_at: csv_timestamp(column(provider_columns + 1), &uid)?,
+        phone_number: csv_field(column(provider_columns + 2)),
+        disabled: column(provider_columns + 3) == "true",
+        custom_claims,
+        mfa_info: Vec::new(),
+        uid,
+    })
+}
+
+impl<R: std::io::Read> Iterator for CsvExportReader<R> {
+    type Item = Result<ExportedUser, Report<MigrationError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.records.next()?;
+
+        Some(
+            record
+                .change_context(MigrationError::FailedToRead)
+                .and_then(|record| parse_csv_user(&record)),
+        )
+    }
+}
+
+/// Writes users into a CSV export in the column layout of the Firebase CLI
+pub struct CsvExportWriter<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> CsvExportWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(writer),
+        }
+    }
+
+    pub fn write_user(&mut self, user: &ExportedUser) -> Result<(), Report<MigrationError>> {
+        let mut row: Vec<String> = vec![
+            user.uid.clone(),
+            user.email.clone().unwrap_or_default(),
+            user.email_verified.to_string(),
+            user.password_hash.clone().unwrap_or_default(),
+            user.salt.clone().unwrap_or_default(),
+            user.display_name.clone().unwrap_or_default(),
+            user.photo_url.clone().unwrap_or_default(),
+        ];
+        for provider_id in CSV_PROVIDERS {
+            match user
+                .provider_user_info
+                .iter()
+                .find(|info| info.provider_id == provider_id)
+            {
+                Some(info) => row.extend([
+                    info.raw_id.clone(),
+                    info.email.clone().unwrap_or_default(),
+                    info.display_name.clone().unwrap_or_default(),
+                    info.photo_url.clone().unwrap_or_default(),
+                ]),
+                None => row.extend(std::iter::repeat_n(String::new(), 4)),
+            }
+        }
+        let custom_claims = match &user.custom_claims {
+            Some(claims) => {
+                serde_json::to_string(claims.get()).change_context(MigrationError::FailedToWrite)?
+            }
+            None => String::new(),
+        };
+        row.extend([
+            csv_timestamp_string(user.created_at),
+            csv_timestamp_string(user.last_login_at),
+            user.phone_number.clone().unwrap_or_default(),
+            user.disabled.to_string(),
+            custom_claims,
+        ]);
+
+        self.writer
+            .write_record(&row)
+            .change_context(MigrationError::FailedToWrite)
+    }
+
+    pub fn finish(self) -> Result<W, Report<MigrationError>> {
+        self.writer
+            .into_inner()
+            .map_err(|e| Report::new(MigrationError::FailedToWrite).attach(e.to_string()))
+    }
+}
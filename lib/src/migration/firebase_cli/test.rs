@@ -0,0 +1,148 @@
+use super::{CsvExportReader, CsvExportWriter, ExportedUser, JsonExportReader, JsonExportWriter};
+use crate::auth::import::{HashConfig, PasswordHash, UserImportRecord};
+use serde_json::{from_value, json, to_value};
+use std::io::Cursor;
+
+const JSON_EXPORT: &str = r#"{"users": [
+  {
+    "localId": "user1",
+    "email": "user1@example.com",
+    "emailVerified": true,
+    "passwordHash": "aGFzaA==",
+    "salt": "c2FsdA==",
+    "lastSignedInAt": "1700000001000",
+    "createdAt": "1700000000000",
+    "disabled": false,
+    "customAttributes": "{\"admin\":true}",
+    "providerUserInfo": [
+      {"providerId": "password", "rawId": "user1@example.com", "email": "user1@example.com"},
+      {"providerId": "google.com", "rawId": "g-1", "displayName": "User One"}
+    ]
+  } ,
+  {"localId": "user2", "phoneNumber": "+11234567890", "disabled": true, "providerUserInfo": []}
+]}
+"#;
+
+#[test]
+fn test_json_export_round_trip() {
+    let users: Vec<ExportedUser> = JsonExportReader::new(Cursor::new(JSON_EXPORT))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0].uid, "user1");
+    assert_eq!(users[0].created_at.unwrap().unix_timestamp(), 1_700_000_000);
+    assert_eq!(
+        users[0].custom_claims.as_ref().unwrap().get()["admin"],
+        true
+    );
+    assert_eq!(users[0].provider_user_info[1].raw_id, "g-1");
+    assert!(users[1].disabled);
+
+    let mut writer = JsonExportWriter::new(Vec::new());
+    for user in &users {
+        writer.write_user(user).unwrap();
+    }
+    let written = writer.finish().unwrap();
+
+    let original: serde_json::Value = serde_json::from_str(JSON_EXPORT).unwrap();
+    let reread: serde_json::Value = serde_json::from_slice(&written).unwrap();
+    assert_eq!(reread["users"][0], original["users"][0]);
+    assert_eq!(reread["users"][1]["phoneNumber"], "+11234567890");
+}
+
+#[test]
+fn test_json_export_empty_and_malformed() {
+    let empty = JsonExportWriter::new(Vec::new()).finish().unwrap();
+    assert_eq!(JsonExportReader::new(Cursor::new(empty)).count(), 0);
+
+    let mut reader = JsonExportReader::new(Cursor::new(r#"{"users": [{"localId": "a"} {"#));
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_csv_export_round_trip() {
+    let users: Vec<ExportedUser> = JsonExportReader::new(Cursor::new(JSON_EXPORT))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let mut writer = CsvExportWriter::new(Vec::new());
+    for user in &users {
+        writer.write_user(user).unwrap();
+    }
+    let written = String::from_utf8(writer.finish().unwrap()).unwrap();
+    let first_row = written.lines().next().unwrap();
+    assert!(
+        first_row.starts_with("user1,user1@example.com,true,aGFzaA==,c2FsdA==,,,g-1,,User One,,")
+    );
+    assert!(first_row.ends_with(",1700000000000,1700000001000,,false,\"{\"\"admin\"\":true}\""));
+
+    let reread: Vec<ExportedUser> = CsvExportReader::new(written.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(reread.len(), 2);
+    assert_eq!(reread[0].provider_user_info.len(), 1);
+    assert_eq!(reread[0].provider_user_info[0].provider_id, "google.com");
+    assert_eq!(reread[0].last_login_at, users[0].last_login_at);
+    assert_eq!(
+        reread[0].custom_claims.as_ref().unwrap().get()["admin"],
+        true
+    );
+    assert_eq!(reread[1].phone_number.as_deref(), Some("+11234567890"));
+    assert!(reread[1].disabled);
+
+    assert!(
+        CsvExportReader::new("user1,too,short".as_bytes())
+            .next()
+            .unwrap()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_exported_user_into_import_record() {
+    let user = JsonExportReader::new(Cursor::new(JSON_EXPORT))
+        .next()
+        .unwrap()
+        .unwrap();
+
+    let record = UserImportRecord::try_from(user).unwrap();
+    let body = to_value(&record).unwrap();
+
+    assert_eq!(body["localId"], "user1");
+    assert_eq!(body["passwordHash"], "aGFzaA==");
+    assert_eq!(body["createdAt"], "1700000000000");
+    assert_eq!(body["lastLoginAt"], "1700000001000");
+    assert_eq!(body["customAttributes"], "{\"admin\":true}");
+    assert_eq!(body["providerUserInfo"].as_array().unwrap().len(), 1);
+    assert_eq!(body["providerUserInfo"][0]["providerId"], "google.com");
+}
+
+#[test]
+fn test_hash_config_into_password_hash() {
+    let config: HashConfig = from_value(json!({
+        "algorithm": "SCRYPT",
+        "base64_signer_key": "a2V5",
+        "base64_salt_separator": "Bw==",
+        "rounds": 8,
+        "mem_cost": 14
+    }))
+    .unwrap();
+
+    assert!(matches!(
+        PasswordHash::try_from(&config).unwrap(),
+        PasswordHash::Scrypt {
+            rounds: 8,
+            memory_cost: 14,
+            ..
+        }
+    ));
+
+    let config = HashConfig {
+        algorithm: "HMAC_SHA256".into(),
+        ..Default::default()
+    };
+    assert!(PasswordHash::try_from(&config).is_err());
+}
@@ -0,0 +1,151 @@
+//! Conversion of user exports into import records and back
+
+pub mod auth0;
+pub mod cognito;
+pub mod firebase_cli;
+pub mod supabase;
+
+use crate::auth::Claims;
+use crate::auth::import::UserImportRecord;
+use crate::validation::ValidationError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use error_stack::{Report, ResultExt};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Read;
+use thiserror::Error;
+
+/// Serialized size limit of custom claims
+const MAX_CLAIMS_BYTES: usize = 1000;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    #[error("Failed to read user export")]
+    FailedToRead,
+    #[error("Failed to write user export")]
+    FailedToWrite,
+    #[error("User export is malformed")]
+    MalformedExport,
+    #[error("Exported user {0} can not be converted")]
+    InvalidRecord(String),
+}
+
+/// Part of an exported user which does not survive the migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationLoss {
+    /// Attribute without counterpart in Firebase, by its name in the export
+    DroppedAttribute(String),
+    /// Password hash in a format which can not be imported, the user has to reset the password
+    UnsupportedPasswordHash,
+    /// Attribute with a value Firebase would reject, by its name in the export
+    InvalidAttribute {
+        name: String,
+        error: ValidationError,
+    },
+    /// Linked identity of a provider not known to Firebase
+    DroppedIdentity {
+        provider: String,
+        user_id: Option<String>,
+    },
+}
+
+/// Import record converted from another identity provider
+#[derive(Debug, Clone)]
+pub struct MigratedUser {
+    pub record: UserImportRecord,
+    pub losses: Vec<MigrationLoss>,
+}
+
+impl MigratedUser {
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+}
+
+/// Exported user which could not be migrated without losses
+#[derive(Debug, Clone)]
+pub struct LossyRecord {
+    /// Position of the record in the export and in `MigrationBatch::records`
+    pub index: usize,
+    pub uid: Option<String>,
+    pub losses: Vec<MigrationLoss>,
+}
+
+/// Converted records ready for `import_users`, together with a report of lossy ones
+#[derive(Debug, Clone, Default)]
+pub struct MigrationBatch {
+    pub records: Vec<UserImportRecord>,
+    pub lossy: Vec<LossyRecord>,
+}
+
+impl FromIterator<MigratedUser> for MigrationBatch {
+    fn from_iter<I: IntoIterator<Item = MigratedUser>>(iter: I) -> Self {
+        let mut batch = Self::default();
+        for (index, user) in iter.into_iter().enumerate() {
+            if !user.is_lossless() {
+                batch.lossy.push(LossyRecord {
+                    index,
+                    uid: user.record.uid.clone(),
+                    losses: user.losses,
+                });
+            }
+            batch.records.push(user.record);
+        }
+
+        batch
+    }
+}
+
+/// Stream newline delimited JSON objects, converting each into a migrated user
+fn read_ndjson<R, T>(
+    reader: R,
+) -> impl Iterator<Item = Result<MigratedUser, Report<MigrationError>>>
+where
+    R: Read,
+    T: DeserializeOwned + Into<MigratedUser>,
+{
+    serde_json::Deserializer::from_reader(reader)
+        .into_iter::<T>()
+        .map(|user| {
+            user.map(Into::into)
+                .change_context(MigrationError::MalformedExport)
+        })
+}
+
+/// Validated attribute, a malformed value is dropped and recorded as lost
+fn validated<T, F>(
+    name: &str,
+    value: String,
+    parse: F,
+    losses: &mut Vec<MigrationLoss>,
+) -> Option<T>
+where
+    F: FnOnce(String) -> Result<T, ValidationError>,
+{
+    parse(value)
+        .map_err(|error| {
+            losses.push(MigrationLoss::InvalidAttribute {
+                name: name.into(),
+                error,
+            })
+        })
+        .ok()
+}
+
+/// Base64 encoded bcrypt hash as expected with `PasswordHash::Bcrypt`, if the hash is bcrypt
+fn bcrypt_hash(hash: &str) -> Option<String> {
+    ["$2a$", "$2b$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+        .then(|| BASE64.encode(hash))
+}
+
+/// Custom claims from a metadata object, if it fits into the claims size limit
+fn metadata_claims(metadata: Map<String, Value>) -> Option<Claims> {
+    let size = serde_json::to_string(&metadata).ok()?.len();
+
+    (size <= MAX_CLAIMS_BYTES)
+        .then(|| Claims::from(metadata.into_iter().collect::<BTreeMap<_, _>>()))
+}
@@ -0,0 +1,158 @@
+//! Supabase (GoTrue) `auth.users` rows as newline delimited JSON, e.g. produced by
+//! `\copy (select row_to_json(u) from auth.users u) to 'users.ndjson'`
+//!
+//! Password hashes are bcrypt, import the records with `UserImportOptions::with_hash(PasswordHash::Bcrypt)`.
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    MigratedUser, MigrationError, MigrationLoss, bcrypt_hash, metadata_claims, read_ndjson,
+    validated,
+};
+use crate::auth::import::UserImportRecord;
+use crate::validation::{Email, PhoneNumber, PhotoUrl, Uid};
+use error_stack::Report;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::io::Read;
+use time::OffsetDateTime;
+
+/// Keys of `raw_app_meta_data` maintained by GoTrue itself
+const APP_METADATA_KEYS: [&str; 2] = ["provider", "providers"];
+/// Keys of `raw_user_meta_data` copied from identities or mapped onto the record
+const USER_METADATA_KEYS: [&str; 10] = [
+    "full_name",
+    "name",
+    "avatar_url",
+    "picture",
+    "email",
+    "email_verified",
+    "phone_verified",
+    "sub",
+    "iss",
+    "provider_id",
+];
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SupabaseUser {
+    pub id: String,
+    pub email: Option<String>,
+    pub encrypted_password: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub email_confirmed_at: Option<OffsetDateTime>,
+    /// Stored without the leading `+`
+    pub phone: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_sign_in_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub banned_until: Option<OffsetDateTime>,
+    pub raw_app_meta_data: Option<Map<String, Value>>,
+    pub raw_user_meta_data: Option<Map<String, Value>>,
+    pub is_anonymous: Option<bool>,
+}
+
+fn metadata_str(metadata: &Map<String, Value>, keys: [&str; 2]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| metadata.get(*key)?.as_str())
+        .map(str::to_string)
+}
+
+impl From<SupabaseUser> for MigratedUser {
+    fn from(user: SupabaseUser) -> Self {
+        let mut losses = Vec::new();
+        let mut builder = UserImportRecord::builder();
+
+        if let Some(uid) = validated("id", user.id, Uid::new, &mut losses) {
+            builder = builder.with_uid(uid);
+        }
+        if let Some(email) = user
+            .email
+            .filter(|email| !email.is_empty())
+            .and_then(|email| validated("email", email, Email::new, &mut losses))
+        {
+            builder = builder.with_email(email, user.email_confirmed_at.is_some());
+        }
+        if let Some(hash) = user.encrypted_password.filter(|hash| !hash.is_empty()) {
+            match bcrypt_hash(&hash) {
+                Some(hash) => builder = builder.with_password_hash(hash, None),
+                None => losses.push(MigrationLoss::UnsupportedPasswordHash),
+            }
+        }
+        if let Some(phone) = user.phone.filter(|phone| !phone.is_empty()) {
+            let phone = match phone.starts_with('+') {
+                true => phone,
+                false => format!("+{phone}"),
+            };
+            if let Some(phone) = validated("phone", phone, PhoneNumber::new, &mut losses) {
+                builder = builder.with_phone_number(phone);
+            }
+        }
+        if let Some(created_at) = user.created_at {
+            builder = builder.with_created_at(created_at);
+        }
+        if let Some(last_sign_in_at) = user.last_sign_in_at {
+            builder = builder.with_last_login_at(last_sign_in_at);
+        }
+        if user
+            .banned_until
+            .is_some_and(|until| until > OffsetDateTime::now_utc())
+        {
+            builder = builder.with_being_disabled();
+        }
+        if user.is_anonymous == Some(true) {
+            losses.push(MigrationLoss::DroppedAttribute("is_anonymous".into()));
+        }
+
+        let mut user_metadata = user.raw_user_meta_data.unwrap_or_default();
+        if let Some(name) = metadata_str(&user_metadata, ["full_name", "name"]) {
+            builder = builder.with_display_name(name);
+        }
+        if let Some(avatar) = metadata_str(&user_metadata, ["avatar_url", "picture"])
+            .and_then(|avatar| validated("avatar_url", avatar, PhotoUrl::new, &mut losses))
+        {
+            builder = builder.with_photo_url(avatar);
+        }
+        user_metadata.retain(|key, _| !USER_METADATA_KEYS.contains(&key.as_str()));
+        losses.extend(
+            user_metadata
+                .keys()
+                .map(|key| MigrationLoss::DroppedAttribute(format!("raw_user_meta_data.{key}"))),
+        );
+
+        let mut app_metadata = user.raw_app_meta_data.unwrap_or_default();
+        // Linked identities live in `auth.identities` and are not part of the row
+        let providers = app_metadata
+            .get("providers")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter(|provider| !["email", "phone"].contains(provider));
+        losses.extend(providers.map(|provider| MigrationLoss::DroppedIdentity {
+            provider: provider.to_string(),
+            user_id: None,
+        }));
+        app_metadata.retain(|key, _| !APP_METADATA_KEYS.contains(&key.as_str()));
+        if !app_metadata.is_empty() {
+            match metadata_claims(app_metadata) {
+                Some(claims) => builder = builder.with_custom_claims(claims),
+                None => losses.push(MigrationLoss::DroppedAttribute("raw_app_meta_data".into())),
+            }
+        }
+
+        Self {
+            record: builder.build(),
+            losses,
+        }
+    }
+}
+
+/// Stream users out of newline delimited `auth.users` rows
+pub fn read_export<R: Read>(
+    reader: R,
+) -> impl Iterator<Item = Result<MigratedUser, Report<MigrationError>>> {
+    read_ndjson::<R, SupabaseUser>(reader)
+}
@@ -0,0 +1,44 @@
+use super::read_export;
+use crate::migration::{MigratedUser, MigrationLoss};
+use serde_json::to_value;
+
+const EXPORT: &str = r#"{"id":"6b2f1c2e-8a47-4a8e-9a3c-6d6f2f1d4b11","email":"user1@example.com","encrypted_password":"$2a$10$abcdefghijklmnopqrstuuMLdlnD5eC7r3W6ybtk5M1qmbWLB8qD2","email_confirmed_at":"2023-05-01T10:00:00.123456+00:00","phone":"15551234567","created_at":"2023-05-01T09:59:00+00:00","last_sign_in_at":null,"banned_until":"2999-01-01T00:00:00+00:00","raw_app_meta_data":{"provider":"email","providers":["email","google"],"role":"editor"},"raw_user_meta_data":{"full_name":"User One","avatar_url":"https://example.com/a.png","sub":"1","newsletter":true}}
+{"id":"0e4c5b8e-2d4f-4f0c-8f7b-3c1d2a6e9f00","email":"","encrypted_password":"","raw_app_meta_data":{"provider":"email","providers":["email"]},"raw_user_meta_data":{}}
+"#;
+
+#[test]
+fn test_supabase_export_conversion() {
+    let users: Vec<MigratedUser> = read_export(EXPORT.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let first = to_value(&users[0].record).unwrap();
+    assert_eq!(first["localId"], "6b2f1c2e-8a47-4a8e-9a3c-6d6f2f1d4b11");
+    assert_eq!(first["emailVerified"], true);
+    assert_eq!(first["phoneNumber"], "+15551234567");
+    assert_eq!(first["displayName"], "User One");
+    assert_eq!(first["photoUrl"], "https://example.com/a.png");
+    assert_eq!(first["disabled"], true);
+    assert_eq!(first["customAttributes"], "{\"role\":\"editor\"}");
+    assert!(
+        first["passwordHash"]
+            .as_str()
+            .unwrap()
+            .starts_with("JDJhJDEw")
+    );
+    assert_eq!(
+        users[0].losses,
+        vec![
+            MigrationLoss::DroppedAttribute("raw_user_meta_data.newsletter".into()),
+            MigrationLoss::DroppedIdentity {
+                provider: "google".into(),
+                user_id: None
+            },
+        ]
+    );
+
+    let second = to_value(&users[1].record).unwrap();
+    assert!(second.get("email").is_none());
+    assert!(second.get("passwordHash").is_none());
+    assert!(users[1].is_lossless());
+}
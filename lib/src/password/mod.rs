@@ -0,0 +1,269 @@
+//! Offline verification of password hashes exported from Firebase projects
+
+#[cfg(test)]
+mod test;
+
+use crate::auth::User;
+use crate::auth::import::PasswordHash;
+use crate::util::encoding::decode_base64;
+use aes::Aes256;
+use ctr::Ctr128BE;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use error_stack::{Report, ResultExt};
+use hmac::digest::Digest;
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+/// Length of the key derived by Firebase modified scrypt
+const FIREBASE_SCRYPT_KEY_LENGTH: usize = 32;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PasswordError {
+    #[error("User has no password hash")]
+    MissingPasswordHash,
+    #[error("{0} is not valid base64")]
+    InvalidBase64(&'static str),
+    #[error("Hash parameters can not be used to derive a key")]
+    InvalidHashParameters,
+}
+
+/// Order in which salt and password are concatenated for MD5, SHA and HMAC hashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashInputOrder {
+    #[default]
+    SaltFirst,
+    PasswordFirst,
+}
+
+/// Verifies plaintext passwords against hashes created with a given algorithm
+/// # Example
+/// ```rust
+/// let verifier = PasswordVerifier::new(PasswordHash::Scrypt {
+///     key: signer_key,
+///     rounds: 8,
+///     memory_cost: 14,
+///     salt_separator: Some("Bw==".into()),
+/// });
+///
+/// let matches = verifier.verify_user("user1password", &user).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswordVerifier {
+    hash: PasswordHash,
+    input_order: HashInputOrder,
+}
+
+impl PasswordVerifier {
+    pub fn new(hash: PasswordHash) -> Self {
+        Self {
+            hash,
+            input_order: HashInputOrder::default(),
+        }
+    }
+
+    pub fn with_input_order(mut self, input_order: HashInputOrder) -> Self {
+        self.input_order = input_order;
+
+        self
+    }
+
+    /// Check password against the hash and salt of a user
+    pub fn verify_user(&self, password: &str, user: &User) -> Result<bool, Report<PasswordError>> {
+        let password_hash = user
+            .password_hash
+            .as_deref()
+            .ok_or(PasswordError::MissingPasswordHash)?;
+
+        self.verify(password, password_hash, user.salt.as_deref())
+    }
+
+    /// Check password against a base64 encoded hash and salt
+    pub fn verify(
+        &self,
+        password: &str,
+        password_hash: &str,
+        salt: Option<&str>,
+    ) -> Result<bool, Report<PasswordError>> {
+        if let PasswordHash::Bcrypt = self.hash {
+            return verify_bcrypt(password, password_hash);
+        }
+
+        let expected = decode(password_hash, "Password hash")?;
+        let salt = salt
+            .map(|salt| decode(salt, "Salt"))
+            .transpose()?
+            .unwrap_or_default();
+        let password = password.as_bytes();
+
+        let computed = match &self.hash {
+            PasswordHash::HmacSha512 { key } => {
+                hmac::<Hmac<Sha512>>(&decode(key, "Key")?, &self.input(password, &salt))?
+            }
+            PasswordHash::HmacSha256 { key } => {
+                hmac::<Hmac<Sha256>>(&decode(key, "Key")?, &self.input(password, &salt))?
+            }
+            PasswordHash::HmacSha1 { key } => {
+                hmac::<Hmac<Sha1>>(&decode(key, "Key")?, &self.input(password, &salt))?
+            }
+            PasswordHash::HmacMd5 { key } => {
+                hmac::<Hmac<Md5>>(&decode(key, "Key")?, &self.input(password, &salt))?
+            }
+            PasswordHash::Md5 { rounds } => digest::<Md5>(&self.input(password, &salt), *rounds)?,
+            PasswordHash::Sha1 { rounds } => digest::<Sha1>(&self.input(password, &salt), *rounds)?,
+            PasswordHash::Sha256 { rounds } => {
+                digest::<Sha256>(&self.input(password, &salt), *rounds)?
+            }
+            PasswordHash::Sha512 { rounds } => {
+                digest::<Sha512>(&self.input(password, &salt), *rounds)?
+            }
+            PasswordHash::PbkdfSha1 { rounds } => {
+                let mut out = vec![0; <Sha1 as Digest>::output_size()];
+                pbkdf2::pbkdf2_hmac::<Sha1>(password, &salt, check_rounds(*rounds)?, &mut out);
+                out
+            }
+            PasswordHash::Pbkdf2Sha256 { rounds } => {
+                let mut out = vec![0; <Sha256 as Digest>::output_size()];
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, &salt, check_rounds(*rounds)?, &mut out);
+                out
+            }
+            PasswordHash::Scrypt {
+                key,
+                rounds,
+                memory_cost,
+                salt_separator,
+            } => {
+                let salt_separator = salt_separator
+                    .as_deref()
+                    .map(|separator| decode(separator, "Salt separator"))
+                    .transpose()?
+                    .unwrap_or_default();
+
+                firebase_scrypt(
+                    password,
+                    &[salt, salt_separator].concat(),
+                    &decode(key, "Key")?,
+                    *rounds,
+                    *memory_cost,
+                )?
+            }
+            PasswordHash::StandardScrypt {
+                block_size,
+                parallelization,
+                memory_cost,
+                dk_len,
+            } => {
+                if !memory_cost.is_power_of_two() {
+                    return Err(Report::new(PasswordError::InvalidHashParameters));
+                }
+                let log_n = u8::try_from(memory_cost.trailing_zeros())
+                    .change_context(PasswordError::InvalidHashParameters)?;
+
+                scrypt(
+                    password,
+                    &salt,
+                    log_n,
+                    *block_size,
+                    *parallelization,
+                    *dk_len as usize,
+                )?
+            }
+            PasswordHash::Bcrypt => unreachable!("bcrypt is verified above"),
+        };
+
+        Ok(computed.ct_eq(&expected).into())
+    }
+
+    fn input(&self, password: &[u8], salt: &[u8]) -> Vec<u8> {
+        match self.input_order {
+            HashInputOrder::SaltFirst => [salt, password].concat(),
+            HashInputOrder::PasswordFirst => [password, salt].concat(),
+        }
+    }
+}
+
+fn decode(value: &str, name: &'static str) -> Result<Vec<u8>, Report<PasswordError>> {
+    decode_base64(value).change_context(PasswordError::InvalidBase64(name))
+}
+
+/// Hash `input` once, then rehash the digest for every further round
+fn digest<D: Digest>(input: &[u8], rounds: u32) -> Result<Vec<u8>, Report<PasswordError>> {
+    let mut out = D::digest(input).to_vec();
+    for _ in 1..check_rounds(rounds)? {
+        out = D::digest(&out).to_vec();
+    }
+
+    Ok(out)
+}
+
+/// Iterated hashes need at least one round to produce anything derived from the password
+fn check_rounds(rounds: u32) -> Result<u32, Report<PasswordError>> {
+    if rounds == 0 {
+        return Err(Report::new(PasswordError::InvalidHashParameters)
+            .attach("Hash rounds must be at least 1"));
+    }
+
+    Ok(rounds)
+}
+
+fn hmac<M: Mac + hmac::digest::KeyInit>(
+    key: &[u8],
+    input: &[u8],
+) -> Result<Vec<u8>, Report<PasswordError>> {
+    let mut mac = <M as hmac::digest::KeyInit>::new_from_slice(key)
+        .change_context(PasswordError::InvalidHashParameters)?;
+    mac.update(input);
+
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+    len: usize,
+) -> Result<Vec<u8>, Report<PasswordError>> {
+    let params = scrypt::Params::new(log_n, r, p, len)
+        .map_err(|_| Report::new(PasswordError::InvalidHashParameters))?;
+    let mut out = vec![0; len];
+    scrypt::scrypt(password, salt, &params, &mut out)
+        .map_err(|_| Report::new(PasswordError::InvalidHashParameters))?;
+
+    Ok(out)
+}
+
+/// Firebase modified scrypt, the signer key encrypted with AES-256-CTR under the scrypt derived key
+fn firebase_scrypt(
+    password: &[u8],
+    salt: &[u8],
+    signer_key: &[u8],
+    rounds: u32,
+    memory_cost: u32,
+) -> Result<Vec<u8>, Report<PasswordError>> {
+    let log_n = u8::try_from(memory_cost).change_context(PasswordError::InvalidHashParameters)?;
+    let derived_key = scrypt(password, salt, log_n, rounds, 1, FIREBASE_SCRYPT_KEY_LENGTH)?;
+
+    let mut cipher = Ctr128BE::<Aes256>::new_from_slices(&derived_key, &[0; 16])
+        .change_context(PasswordError::InvalidHashParameters)?;
+    let mut out = signer_key.to_vec();
+    cipher.apply_keystream(&mut out);
+
+    Ok(out)
+}
+
+/// Bcrypt hashes are accepted either as the modular crypt string or base64 encoded
+fn verify_bcrypt(password: &str, password_hash: &str) -> Result<bool, Report<PasswordError>> {
+    let encoded = if password_hash.starts_with("$2") {
+        password_hash.to_string()
+    } else {
+        String::from_utf8(decode(password_hash, "Password hash")?)
+            .change_context(PasswordError::InvalidBase64("Password hash"))?
+    };
+
+    bcrypt::verify(password, &encoded).change_context(PasswordError::InvalidHashParameters)
+}
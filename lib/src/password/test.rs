@@ -0,0 +1,216 @@
+use super::{HashInputOrder, PasswordError, PasswordVerifier};
+use crate::auth::User;
+use crate::auth::import::PasswordHash;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::json;
+
+const PASSWORD: &str = "correct horse";
+const SALT: &str = "TmFDbA==";
+const KEY: &str = "c2lnbmVyLWtleQ==";
+
+// Sample from Firebase's scrypt reference implementation
+const FIREBASE_SIGNER_KEY: &str =
+    "jxspr8Ki0RYycVU8zykbdLGjFQ3McFUH0uiiTvC8pVMXAn210wjLNmdZJzxUECKbm0QsEmYUSDzZvpjeJ9WmXA==";
+const FIREBASE_SALT: &str = "42xEC+ixf3L2lw==";
+const FIREBASE_HASH: &str =
+    "lSrfV15cpx95/sZS2W9c9Kp6i/LVgQNDNC/qzrCnh1SAyZvqmZqAjTdn3aoItz+VHjoZilo78198JAdRuid5lQ==";
+
+fn firebase_scrypt() -> PasswordVerifier {
+    PasswordVerifier::new(PasswordHash::Scrypt {
+        key: FIREBASE_SIGNER_KEY.into(),
+        rounds: 8,
+        memory_cost: 14,
+        salt_separator: Some("Bw==".into()),
+    })
+}
+
+#[test]
+fn test_firebase_scrypt() {
+    let verifier = firebase_scrypt();
+
+    assert!(
+        verifier
+            .verify("user1password", FIREBASE_HASH, Some(FIREBASE_SALT))
+            .unwrap()
+    );
+    assert!(
+        !verifier
+            .verify("user2password", FIREBASE_HASH, Some(FIREBASE_SALT))
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_verify_user() {
+    let user: User = serde_json::from_value(json!({
+        "localId": "user1",
+        // Exported hashes use the URL safe alphabet
+        "passwordHash": FIREBASE_HASH.replace('+', "-").replace('/', "_"),
+        "salt": FIREBASE_SALT.replace('+', "-"),
+    }))
+    .unwrap();
+
+    assert!(
+        firebase_scrypt()
+            .verify_user("user1password", &user)
+            .unwrap()
+    );
+
+    let no_password: User = serde_json::from_value(json!({"localId": "user2"})).unwrap();
+    let report = firebase_scrypt()
+        .verify_user("user1password", &no_password)
+        .unwrap_err();
+    assert_eq!(
+        report.current_context(),
+        &PasswordError::MissingPasswordHash
+    );
+}
+
+#[test]
+fn test_standard_algorithms() {
+    let cases = [
+        (
+            PasswordHash::Sha256 { rounds: 10 },
+            HashInputOrder::SaltFirst,
+            "ZVMLJLd30KyV7F7LbeOrCSdmbp5j/QxakjITtXZeDyM=",
+        ),
+        (
+            PasswordHash::Md5 { rounds: 1 },
+            HashInputOrder::PasswordFirst,
+            "WvNhLLitFNNM0XshzutEnQ==",
+        ),
+        (
+            PasswordHash::HmacSha512 { key: KEY.into() },
+            HashInputOrder::SaltFirst,
+            "VevoCG+x+KlCsCTA2OKe9nU6TuJCQB4XcXnv3OGw8mmJ1PSE8qoLBVBnXAf8I9iLF0XlDRIAACFs/5KZjVYJIg==",
+        ),
+        (
+            PasswordHash::HmacMd5 { key: KEY.into() },
+            HashInputOrder::PasswordFirst,
+            "JtR+wC5QtgIXHpQtg5tR5w==",
+        ),
+        (
+            PasswordHash::Pbkdf2Sha256 { rounds: 1000 },
+            HashInputOrder::SaltFirst,
+            "y6UK+57OG2yJnZIbS3E5E599IliwIIbBdkxEFXXGYlM=",
+        ),
+        (
+            PasswordHash::PbkdfSha1 { rounds: 1000 },
+            HashInputOrder::SaltFirst,
+            "wTijAypcehTb83ax3kkR/eHCLg0=",
+        ),
+        (
+            PasswordHash::StandardScrypt {
+                block_size: 8,
+                parallelization: 1,
+                memory_cost: 1024,
+                dk_len: 32,
+            },
+            HashInputOrder::SaltFirst,
+            "xPqUjhGxb1BHAUIHq9nbFDlZKbsN1Xtol93brViIQhs=",
+        ),
+    ];
+
+    for (hash, order, expected) in cases {
+        let verifier = PasswordVerifier::new(hash.clone()).with_input_order(order);
+
+        assert!(
+            verifier.verify(PASSWORD, expected, Some(SALT)).unwrap(),
+            "{hash:?}"
+        );
+        assert!(
+            !verifier.verify("wrong", expected, Some(SALT)).unwrap(),
+            "{hash:?}"
+        );
+    }
+}
+
+#[test]
+fn test_bcrypt() {
+    let hashed = bcrypt::hash(PASSWORD, 4).unwrap();
+    let verifier = PasswordVerifier::new(PasswordHash::Bcrypt);
+
+    assert!(verifier.verify(PASSWORD, &hashed, None).unwrap());
+    assert!(
+        verifier
+            .verify(PASSWORD, &STANDARD.encode(&hashed), None)
+            .unwrap()
+    );
+    assert!(!verifier.verify("wrong", &hashed, None).unwrap());
+}
+
+#[test]
+fn test_truncated_pbkdf_hash_rejected() {
+    let cases = [
+        (
+            PasswordHash::Pbkdf2Sha256 { rounds: 1000 },
+            "y6UK+57OG2yJnZIbS3E5E599IliwIIbBdkxEFXXGYlM=",
+        ),
+        (
+            PasswordHash::PbkdfSha1 { rounds: 1000 },
+            "wTijAypcehTb83ax3kkR/eHCLg0=",
+        ),
+    ];
+
+    for (hash, expected) in cases {
+        let verifier = PasswordVerifier::new(hash.clone());
+        let truncated = STANDARD.encode(&STANDARD.decode(expected).unwrap()[..4]);
+
+        assert!(
+            !verifier.verify(PASSWORD, "", Some(SALT)).unwrap(),
+            "{hash:?}"
+        );
+        assert!(
+            !verifier.verify(PASSWORD, &truncated, Some(SALT)).unwrap(),
+            "{hash:?}"
+        );
+        assert!(
+            !verifier.verify("wrong", "", Some(SALT)).unwrap(),
+            "{hash:?}"
+        );
+    }
+}
+
+#[test]
+fn test_zero_rounds_rejected() {
+    let hashes = [
+        PasswordHash::Md5 { rounds: 0 },
+        PasswordHash::Sha256 { rounds: 0 },
+        PasswordHash::PbkdfSha1 { rounds: 0 },
+        PasswordHash::Pbkdf2Sha256 { rounds: 0 },
+    ];
+
+    for hash in hashes {
+        let report = PasswordVerifier::new(hash.clone())
+            .verify(PASSWORD, "AAAA", Some(SALT))
+            .unwrap_err();
+        assert_eq!(
+            report.current_context(),
+            &PasswordError::InvalidHashParameters,
+            "{hash:?}"
+        );
+    }
+}
+
+#[test]
+fn test_invalid_inputs() {
+    let verifier = PasswordVerifier::new(PasswordHash::Sha256 { rounds: 1 });
+    let report = verifier.verify(PASSWORD, "not base64!", None).unwrap_err();
+    assert_eq!(
+        report.current_context(),
+        &PasswordError::InvalidBase64("Password hash")
+    );
+
+    let verifier = PasswordVerifier::new(PasswordHash::StandardScrypt {
+        block_size: 8,
+        parallelization: 1,
+        memory_cost: 1000,
+        dk_len: 32,
+    });
+    let report = verifier.verify(PASSWORD, "AAAA", Some(SALT)).unwrap_err();
+    assert_eq!(
+        report.current_context(),
+        &PasswordError::InvalidHashParameters
+    );
+}
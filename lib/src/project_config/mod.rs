@@ -0,0 +1,292 @@
+//! Identity Platform project authentication configuration
+
+#[cfg(test)]
+mod test;
+
+use crate::api_uri::{ApiUriBuilder, ProjectConfigRestApi};
+use crate::auth::FIREBASE_AUTH_REST_AUTHORITY;
+use crate::auth::import::HashConfig;
+use crate::client::ApiHttpClient;
+use crate::client::error::ApiClientError;
+use crate::tenant::{MultiFactorProvider, MultiFactorState};
+use crate::util::update_mask::{MaskedUpdate, non_empty_update_mask};
+use error_stack::Report;
+use http::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailSignInConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Whether email sign in requires a password, otherwise email link is used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_required: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PhoneSignInConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Phone numbers mapped to their fixed verification codes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_phone_numbers: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymousSignInConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SignInConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailSignInConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<PhoneSignInConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous: Option<AnonymousSignInConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_duplicate_emails: Option<bool>,
+    /// Hash configuration of password users, read only
+    #[serde(skip_serializing)]
+    pub hash_config: Option<HashConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailPrivacyConfig {
+    /// Email enumeration protection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_improved_email_privacy: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PasswordPolicyEnforcementState {
+    PasswordPolicyEnforcementStateUnspecified,
+    Off,
+    Enforce,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomStrengthOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_password_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_password_length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains_lowercase_character: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains_uppercase_character: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains_numeric_character: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contains_non_alphanumeric_character: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordPolicyVersion {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_strength_options: Option<CustomStrengthOptions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordPolicyConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_policy_enforcement_state: Option<PasswordPolicyEnforcementState>,
+    /// Only the first version is applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_policy_versions: Option<Vec<PasswordPolicyVersion>>,
+    /// Require users with non-compliant passwords to change them on sign in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_upgrade_on_signin: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpProviderConfig {
+    /// Number of adjacent 30 second intervals accepted, 0 to 10
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adjacent_intervals: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiFactorProviderConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<MultiFactorState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub totp_provider_config: Option<TotpProviderConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiFactorAuthConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<MultiFactorState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_providers: Option<Vec<MultiFactorProvider>>,
+    /// Second factors other than SMS, e.g. TOTP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_configs: Option<Vec<MultiFactorProviderConfig>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowByDefault {
+    /// ISO 3166 region codes SMS can not be sent to
+    #[serde(default)]
+    pub disallowed_regions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowlistOnly {
+    /// ISO 3166 region codes SMS can be sent to
+    #[serde(default)]
+    pub allowed_regions: Vec<String>,
+}
+
+/// Regions SMS can be sent to, only one of the policies can be set
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SmsRegionConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_by_default: Option<AllowByDefault>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowlist_only: Option<AllowlistOnly>,
+}
+
+/// Authentication configuration of a project, when patching only set fields are updated
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfig {
+    /// Resource name, `projects/{project_id}/config`
+    #[serde(skip_serializing)]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign_in: Option<SignInConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_privacy_config: Option<EmailPrivacyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_policy_config: Option<PasswordPolicyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa: Option<MultiFactorAuthConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_domains: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sms_region_config: Option<SmsRegionConfig>,
+}
+
+impl MaskedUpdate for ProjectConfig {
+    const ATOMIC_FIELDS: &'static [&'static str] =
+        &["signIn.phoneNumber.testPhoneNumbers", "smsRegionConfig"];
+}
+
+pub trait ProjectConfigService<ApiHttpClientT>: Send + Sync + 'static
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    fn get_client(&self) -> &ApiHttpClientT;
+    fn get_project_config_uri_builder(&self) -> &ApiUriBuilder;
+
+    /// Get current project configuration
+    fn get_project_config(
+        &self,
+    ) -> impl Future<Output = Result<ProjectConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_project_config_uri_builder();
+
+            client
+                .send_request(uri_builder.build(ProjectConfigRestApi::Config), Method::GET)
+                .await
+        }
+    }
+
+    /// Update project configuration fields which are set in `configuration`
+    /// # Example
+    /// ```rust
+    /// let config = project_config.patch_project_config(ProjectConfig {
+    ///     authorized_domains: Some(vec!["example.com".into()]),
+    ///     email_privacy_config: Some(EmailPrivacyConfig {
+    ///         enable_improved_email_privacy: Some(true),
+    ///     }),
+    ///     ..Default::default()
+    /// }).await.unwrap();
+    /// ```
+    fn patch_project_config(
+        &self,
+        configuration: ProjectConfig,
+    ) -> impl Future<Output = Result<ProjectConfig, Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_project_config_uri_builder();
+            let mask = non_empty_update_mask(&configuration)?;
+
+            client
+                .send_request_body(
+                    uri_builder.build(ProjectConfigRestApi::Config)
+                        + "?updateMask="
+                        + &urlencoding::encode(&mask),
+                    Method::PATCH,
+                    configuration,
+                )
+                .await
+        }
+    }
+}
+
+pub struct ProjectConfigManager<ApiHttpClientT> {
+    client: ApiHttpClientT,
+    project_config_uri_builder: ApiUriBuilder,
+}
+
+impl<ApiHttpClientT> ProjectConfigManager<ApiHttpClientT>
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    /// Create project configuration manager for emulator
+    pub fn emulated(emulator_url: String, project_id: &str, client: ApiHttpClientT) -> Self {
+        let root =
+            emulator_url + &format!("/{FIREBASE_AUTH_REST_AUTHORITY}/v2/projects/{project_id}");
+
+        Self {
+            client,
+            project_config_uri_builder: ApiUriBuilder::new(root),
+        }
+    }
+
+    /// Create project configuration manager for live project
+    pub fn live(project_id: &str, client: ApiHttpClientT) -> Self {
+        let root = format!("https://{FIREBASE_AUTH_REST_AUTHORITY}/v2/projects/{project_id}");
+
+        Self {
+            client,
+            project_config_uri_builder: ApiUriBuilder::new(root),
+        }
+    }
+}
+
+impl<ApiHttpClientT> ProjectConfigService<ApiHttpClientT> for ProjectConfigManager<ApiHttpClientT>
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    fn get_client(&self) -> &ApiHttpClientT {
+        &self.client
+    }
+
+    fn get_project_config_uri_builder(&self) -> &ApiUriBuilder {
+        &self.project_config_uri_builder
+    }
+}
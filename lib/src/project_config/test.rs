@@ -0,0 +1,178 @@
+use super::{
+    AllowlistOnly, CustomStrengthOptions, EmailPrivacyConfig, MultiFactorAuthConfig,
+    MultiFactorProviderConfig, PasswordPolicyConfig, PasswordPolicyEnforcementState,
+    PasswordPolicyVersion, PhoneSignInConfig, ProjectConfig, ProjectConfigService, SignInConfig,
+    SmsRegionConfig, TotpProviderConfig,
+};
+use crate::App;
+use crate::client::error::ApiClientError;
+use crate::tenant::{MultiFactorProvider, MultiFactorState};
+use crate::util::test_server::TestServer;
+use serde_json::{Value, from_slice, json};
+use std::collections::BTreeMap;
+
+const CONFIG_PATH: &str =
+    "/identitytoolkit.googleapis.com/v2/projects/demo-firebase-project/config";
+
+fn config_json() -> Value {
+    json!({
+        "name": "projects/demo-firebase-project/config",
+        "signIn": {
+            "email": {"enabled": true, "passwordRequired": true},
+            "phoneNumber": {"enabled": true, "testPhoneNumbers": {"+11234567890": "123456"}},
+            "anonymous": {"enabled": false},
+            "hashConfig": {"algorithm": "SCRYPT"}
+        },
+        "emailPrivacyConfig": {"enableImprovedEmailPrivacy": true},
+        "passwordPolicyConfig": {
+            "passwordPolicyEnforcementState": "ENFORCE",
+            "passwordPolicyVersions": [{"customStrengthOptions": {"minPasswordLength": 10}}]
+        },
+        "mfa": {
+            "state": "ENABLED",
+            "enabledProviders": ["PHONE_SMS"],
+            "providerConfigs": [{"state": "ENABLED", "totpProviderConfig": {"adjacentIntervals": 5}}]
+        },
+        "authorizedDomains": ["localhost", "example.com"],
+        "smsRegionConfig": {"allowlistOnly": {"allowedRegions": ["US", "CA"]}}
+    })
+}
+
+#[tokio::test]
+async fn test_get_project_config() {
+    let (server, manager) = TestServer::json_stub_client(config_json(), |url| {
+        App::emulated().project_config_manager(url)
+    })
+    .await;
+
+    let config = manager.get_project_config().await.unwrap();
+
+    let sign_in = config.sign_in.unwrap();
+    assert_eq!(sign_in.email.unwrap().password_required, Some(true));
+    assert_eq!(
+        sign_in.phone_number.unwrap().test_phone_numbers.unwrap()["+11234567890"],
+        "123456"
+    );
+    assert_eq!(
+        config
+            .email_privacy_config
+            .unwrap()
+            .enable_improved_email_privacy,
+        Some(true)
+    );
+    let password_policy = config.password_policy_config.unwrap();
+    assert_eq!(
+        password_policy.password_policy_enforcement_state,
+        Some(PasswordPolicyEnforcementState::Enforce)
+    );
+    let mfa = config.mfa.unwrap();
+    assert_eq!(mfa.state, Some(MultiFactorState::Enabled));
+    assert_eq!(
+        mfa.provider_configs.unwrap()[0]
+            .totp_provider_config
+            .as_ref()
+            .unwrap()
+            .adjacent_intervals,
+        Some(5)
+    );
+    assert_eq!(config.authorized_domains.unwrap().len(), 2);
+    assert_eq!(
+        config
+            .sms_region_config
+            .unwrap()
+            .allowlist_only
+            .unwrap()
+            .allowed_regions,
+        vec!["US", "CA"]
+    );
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "GET");
+    assert_eq!(request.path, CONFIG_PATH);
+}
+
+#[tokio::test]
+async fn test_patch_project_config() {
+    let (server, manager) = TestServer::json_stub_client(config_json(), |url| {
+        App::emulated().project_config_manager(url)
+    })
+    .await;
+
+    manager
+        .patch_project_config(ProjectConfig {
+            sign_in: Some(SignInConfig {
+                phone_number: Some(PhoneSignInConfig {
+                    test_phone_numbers: Some(BTreeMap::from([(
+                        "+11234567890".into(),
+                        "654321".into(),
+                    )])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            email_privacy_config: Some(EmailPrivacyConfig {
+                enable_improved_email_privacy: Some(false),
+            }),
+            password_policy_config: Some(PasswordPolicyConfig {
+                password_policy_versions: Some(vec![PasswordPolicyVersion {
+                    custom_strength_options: Some(CustomStrengthOptions {
+                        min_password_length: Some(12),
+                        ..Default::default()
+                    }),
+                }]),
+                ..Default::default()
+            }),
+            mfa: Some(MultiFactorAuthConfig {
+                enabled_providers: Some(vec![MultiFactorProvider::PhoneSms]),
+                provider_configs: Some(vec![MultiFactorProviderConfig {
+                    state: Some(MultiFactorState::Enabled),
+                    totp_provider_config: Some(TotpProviderConfig {
+                        adjacent_intervals: Some(3),
+                    }),
+                }]),
+                ..Default::default()
+            }),
+            authorized_domains: Some(vec!["example.com".into()]),
+            sms_region_config: Some(SmsRegionConfig {
+                allowlist_only: Some(AllowlistOnly {
+                    allowed_regions: vec!["US".into()],
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "PATCH");
+    let (path, query) = request.path.split_once('?').unwrap();
+    assert_eq!(path, CONFIG_PATH);
+    let mask = urlencoding::decode(query.strip_prefix("updateMask=").unwrap()).unwrap();
+    assert_eq!(
+        mask,
+        "authorizedDomains,emailPrivacyConfig.enableImprovedEmailPrivacy,\
+         mfa.enabledProviders,mfa.providerConfigs,passwordPolicyConfig.passwordPolicyVersions,\
+         signIn.phoneNumber.testPhoneNumbers,smsRegionConfig"
+    );
+
+    let body: Value = from_slice(&request.body).unwrap();
+    assert_eq!(body["authorizedDomains"], json!(["example.com"]));
+    assert!(body.get("name").is_none());
+}
+
+#[tokio::test]
+async fn test_empty_project_config_patch_rejected() {
+    let (server, manager) = TestServer::json_stub_client(config_json(), |url| {
+        App::emulated().project_config_manager(url)
+    })
+    .await;
+
+    let err = manager
+        .patch_project_config(ProjectConfig::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err.current_context(), ApiClientError::EmptyUpdate));
+    assert!(server.requests().is_empty());
+}
@@ -0,0 +1,354 @@
+//! Signing backends for custom tokens and other signed artifacts
+
+#[cfg(test)]
+mod test;
+
+use crate::api_uri::{ApiUriBuilder, IamCredentialsRestApi};
+use crate::client::{ApiHttpClient, ReqwestApiClient};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use error_stack::{Report, ResultExt};
+use http::Method;
+use rsa::RsaPrivateKey;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::sha2::Sha256;
+use rsa::signature::{SignatureEncoding, Signer as _};
+use serde::{Deserialize, Serialize};
+use std::env::var;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::OnceCell;
+
+const EMULATOR_SERVICE_ACCOUNT: &str = "firebase-auth-emulator@example.com";
+const SERVICE_ACCOUNT_KEY_TYPE: &str = "service_account";
+const IAM_CREDENTIALS_REST_AUTHORITY: &str = "iamcredentials.googleapis.com";
+const METADATA_SERVICE_ACCOUNT_EMAIL_PATH: &str =
+    "/computeMetadata/v1/instance/service-accounts/default/email";
+
+#[derive(Error, Debug, Clone)]
+pub enum SignerError {
+    #[error("Failed to load signing key")]
+    InvalidKey,
+    #[error("Failed to sign data")]
+    FailedToSign,
+    #[error("Failed to resolve service account")]
+    UnknownAccount,
+}
+
+/// Produces signatures on behalf of a service account
+pub trait Signer: Send + Sync + 'static {
+    /// JWS algorithm name of produced signatures, as used in the JWT `alg` header
+    fn algorithm(&self) -> &'static str;
+
+    /// Email of the service account signatures are attributed to
+    fn account_id(&self) -> impl Future<Output = Result<String, Report<SignerError>>> + Send;
+
+    /// Sign arbitrary bytes, returning raw signature bytes
+    fn sign(
+        &self,
+        data: &[u8],
+    ) -> impl Future<Output = Result<Vec<u8>, Report<SignerError>>> + Send;
+}
+
+#[derive(Deserialize)]
+struct CredentialsType {
+    #[serde(rename = "type")]
+    key_type: String,
+}
+
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    #[serde(rename = "type")]
+    key_type: String,
+    client_email: String,
+    private_key: String,
+}
+
+/// Signs with a local RSA key of a service account
+#[derive(Debug, Clone)]
+pub struct ServiceAccountSigner {
+    client_email: String,
+    signing_key: Arc<SigningKey<Sha256>>,
+}
+
+impl ServiceAccountSigner {
+    /// Create signer from a PEM encoded RSA private key
+    pub fn new(client_email: String, private_key_pem: &str) -> Result<Self, Report<SignerError>> {
+        let key = RsaPrivateKey::from_pkcs8_pem(private_key_pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(private_key_pem))
+            .change_context(SignerError::InvalidKey)?;
+
+        Ok(Self {
+            client_email,
+            signing_key: Arc::new(SigningKey::new(key)),
+        })
+    }
+
+    /// Create signer from a service account JSON key
+    pub fn from_json(json: &str) -> Result<Self, Report<SignerError>> {
+        let key: ServiceAccountKey =
+            serde_json::from_str(json).change_context(SignerError::InvalidKey)?;
+
+        if key.key_type != SERVICE_ACCOUNT_KEY_TYPE {
+            return Err(Report::new(SignerError::InvalidKey)
+                .attach(format!("Unexpected credentials type {}", key.key_type)));
+        }
+
+        Self::new(key.client_email, &key.private_key)
+    }
+
+    /// Create signer from a service account JSON key file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Report<SignerError>> {
+        let json = std::fs::read_to_string(path).change_context(SignerError::InvalidKey)?;
+
+        Self::from_json(&json)
+    }
+
+    /// Create signer from the file referenced by `GOOGLE_APPLICATION_CREDENTIALS`,
+    /// yields `None` when it is unset or references credentials of another type, e.g. `authorized_user`.
+    /// Fails when the file can not be read or parsed.
+    pub fn from_env() -> Result<Option<Self>, Report<SignerError>> {
+        match var("GOOGLE_APPLICATION_CREDENTIALS") {
+            Ok(path) => Self::from_credentials_file(path),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn from_credentials_file<P: AsRef<Path>>(path: P) -> Result<Option<Self>, Report<SignerError>> {
+        let json = std::fs::read_to_string(path).change_context(SignerError::InvalidKey)?;
+        let credentials: CredentialsType =
+            serde_json::from_str(&json).change_context(SignerError::InvalidKey)?;
+        if credentials.key_type != SERVICE_ACCOUNT_KEY_TYPE {
+            return Ok(None);
+        }
+
+        Self::from_json(&json).map(Some)
+    }
+}
+
+impl Signer for ServiceAccountSigner {
+    fn algorithm(&self) -> &'static str {
+        "RS256"
+    }
+
+    async fn account_id(&self) -> Result<String, Report<SignerError>> {
+        Ok(self.client_email.clone())
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Report<SignerError>> {
+        let signature = self
+            .signing_key
+            .try_sign(data)
+            .change_context(SignerError::FailedToSign)?;
+
+        Ok(signature.to_vec())
+    }
+}
+
+/// Produces empty signatures, as accepted by Firebase emulator
+#[derive(Debug, Clone, Default)]
+pub struct EmulatorSigner;
+
+impl Signer for EmulatorSigner {
+    fn algorithm(&self) -> &'static str {
+        "none"
+    }
+
+    async fn account_id(&self) -> Result<String, Report<SignerError>> {
+        Ok(EMULATOR_SERVICE_ACCOUNT.into())
+    }
+
+    async fn sign(&self, _data: &[u8]) -> Result<Vec<u8>, Report<SignerError>> {
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct SignBlobRequest {
+    payload: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SignBlobResponse {
+    signed_blob: String,
+}
+
+/// Source of the default service account of the compute instance
+pub trait MetadataClient: Send + Sync + 'static {
+    /// Email of the default service account
+    fn service_account_email(
+        &self,
+    ) -> impl Future<Output = Result<String, Report<SignerError>>> + Send;
+}
+
+/// GCE metadata server client
+#[derive(Debug, Clone)]
+pub struct GceMetadataClient {
+    client: reqwest::Client,
+    host: String,
+}
+
+impl GceMetadataClient {
+    /// Create client for metadata server on given host
+    pub fn new(client: reqwest::Client, host: String) -> Self {
+        Self { client, host }
+    }
+
+    /// Create client for metadata server of the compute instance,
+    /// host can be overridden with `GCE_METADATA_HOST` environment variable
+    pub fn from_env(client: reqwest::Client) -> Self {
+        Self::new(
+            client,
+            var("GCE_METADATA_HOST").unwrap_or_else(|_| "metadata.google.internal".into()),
+        )
+    }
+}
+
+impl MetadataClient for GceMetadataClient {
+    async fn service_account_email(&self) -> Result<String, Report<SignerError>> {
+        let resp = self
+            .client
+            .get(format!(
+                "http://{}{METADATA_SERVICE_ACCOUNT_EMAIL_PATH}",
+                self.host
+            ))
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .change_context(SignerError::UnknownAccount)?
+            .error_for_status()
+            .change_context(SignerError::UnknownAccount)?;
+
+        resp.text()
+            .await
+            .change_context(SignerError::UnknownAccount)
+    }
+}
+
+/// Signs remotely through IAM Credentials `signBlob` API,
+/// for credentials that do not expose a private key, e.g. provided by metadata server.
+/// Caller needs `iam.serviceAccounts.signBlob` permission on the service account.
+pub struct IamSigner<ApiHttpClientT, MetadataClientT = GceMetadataClient> {
+    client: ApiHttpClientT,
+    account_id: OnceCell<String>,
+    metadata: Option<MetadataClientT>,
+    iam_uri_root: String,
+}
+
+impl<ApiHttpClientT> IamSigner<ApiHttpClientT>
+where
+    ApiHttpClientT: ApiHttpClient,
+{
+    /// Create signer for given service account
+    pub fn new(client: ApiHttpClientT, service_account_email: String) -> Self {
+        Self {
+            client,
+            account_id: OnceCell::new_with(Some(service_account_email)),
+            metadata: None,
+            iam_uri_root: format!("https://{IAM_CREDENTIALS_REST_AUTHORITY}"),
+        }
+    }
+}
+
+impl<ApiHttpClientT, MetadataClientT> IamSigner<ApiHttpClientT, MetadataClientT>
+where
+    ApiHttpClientT: ApiHttpClient,
+    MetadataClientT: MetadataClient,
+{
+    /// Create signer for the default service account of the compute instance,
+    /// its email is resolved through metadata server on first use
+    pub fn from_metadata_server(client: ApiHttpClientT, metadata: MetadataClientT) -> Self {
+        Self {
+            client,
+            account_id: OnceCell::new(),
+            metadata: Some(metadata),
+            iam_uri_root: format!("https://{IAM_CREDENTIALS_REST_AUTHORITY}"),
+        }
+    }
+
+    async fn fetch_account_id(&self) -> Result<String, Report<SignerError>> {
+        match &self.metadata {
+            Some(metadata) => metadata.service_account_email().await,
+            None => Err(Report::new(SignerError::UnknownAccount)),
+        }
+    }
+}
+
+impl<ApiHttpClientT, MetadataClientT> Signer for IamSigner<ApiHttpClientT, MetadataClientT>
+where
+    ApiHttpClientT: ApiHttpClient,
+    MetadataClientT: MetadataClient,
+{
+    fn algorithm(&self) -> &'static str {
+        "RS256"
+    }
+
+    async fn account_id(&self) -> Result<String, Report<SignerError>> {
+        self.account_id
+            .get_or_try_init(|| self.fetch_account_id())
+            .await
+            .cloned()
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Report<SignerError>> {
+        let account_id = self.account_id().await?;
+        let uri_builder = ApiUriBuilder::new(format!(
+            "{}/v1/projects/-/serviceAccounts/{}",
+            self.iam_uri_root,
+            urlencoding::encode(&account_id)
+        ));
+
+        let resp: SignBlobResponse = self
+            .client
+            .send_request_body(
+                uri_builder.build(IamCredentialsRestApi::SignBlob),
+                Method::POST,
+                SignBlobRequest {
+                    payload: STANDARD.encode(data),
+                },
+            )
+            .await
+            .change_context(SignerError::FailedToSign)?;
+
+        STANDARD
+            .decode(resp.signed_blob)
+            .change_context(SignerError::FailedToSign)
+    }
+}
+
+/// Signer resolved from application credentials
+pub enum CredentialsSigner {
+    ServiceAccount(ServiceAccountSigner),
+    Iam(IamSigner<ReqwestApiClient>),
+    Emulator(EmulatorSigner),
+}
+
+impl Signer for CredentialsSigner {
+    fn algorithm(&self) -> &'static str {
+        match self {
+            Self::ServiceAccount(signer) => signer.algorithm(),
+            Self::Iam(signer) => signer.algorithm(),
+            Self::Emulator(signer) => signer.algorithm(),
+        }
+    }
+
+    async fn account_id(&self) -> Result<String, Report<SignerError>> {
+        match self {
+            Self::ServiceAccount(signer) => signer.account_id().await,
+            Self::Iam(signer) => signer.account_id().await,
+            Self::Emulator(signer) => signer.account_id().await,
+        }
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, Report<SignerError>> {
+        match self {
+            Self::ServiceAccount(signer) => signer.sign(data).await,
+            Self::Iam(signer) => signer.sign(data).await,
+            Self::Emulator(signer) => signer.sign(data).await,
+        }
+    }
+}
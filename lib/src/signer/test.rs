@@ -0,0 +1,218 @@
+use super::{
+    EmulatorSigner, GceMetadataClient, IamSigner, MetadataClient, ServiceAccountSigner, Signer,
+    SignerError,
+};
+use crate::client::ReqwestApiClient;
+use crate::credentials::emulator::EmulatorCredentials;
+use crate::util::test_server::{StubResponse, TestServer};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use error_stack::Report;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::sha2::Sha256;
+use rsa::signature::Verifier;
+use serde_json::{Value, from_slice};
+use tokio::sync::OnceCell;
+
+const TEST_SERVICE_ACCOUNT: &str = include_str!("test_service_account.json");
+
+#[tokio::test]
+async fn test_service_account_signer() {
+    let signer = ServiceAccountSigner::from_json(TEST_SERVICE_ACCOUNT).unwrap();
+
+    assert_eq!(signer.algorithm(), "RS256");
+    assert_eq!(
+        signer.account_id().await.unwrap(),
+        "firebase-adminsdk@demo-firebase-project.iam.gserviceaccount.com"
+    );
+
+    let signature = signer.sign(b"hello").await.unwrap();
+    let verifying_key =
+        VerifyingKey::<Sha256>::new(signer.signing_key.as_ref().as_ref().to_public_key());
+    let signature = Signature::try_from(signature.as_slice()).unwrap();
+
+    verifying_key.verify(b"hello", &signature).unwrap();
+    assert!(verifying_key.verify(b"world", &signature).is_err());
+}
+
+#[test]
+fn test_service_account_signer_rejects_other_credentials() {
+    let json = r#"{
+        "type": "authorized_user",
+        "client_email": "me@example.com",
+        "private_key": ""
+    }"#;
+
+    assert!(ServiceAccountSigner::from_json(json).is_err());
+}
+
+#[test]
+fn test_service_account_signer_from_credentials_file() {
+    let dir = std::env::temp_dir().join(format!("signer-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let user_credentials = dir.join("authorized_user.json");
+    std::fs::write(&user_credentials, r#"{"type": "authorized_user"}"#).unwrap();
+    let malformed = dir.join("malformed.json");
+    std::fs::write(&malformed, "{").unwrap();
+
+    let service_account = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/src/signer/test_service_account.json"
+    );
+    assert!(
+        ServiceAccountSigner::from_credentials_file(service_account)
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        ServiceAccountSigner::from_credentials_file(&user_credentials)
+            .unwrap()
+            .is_none()
+    );
+    assert!(ServiceAccountSigner::from_credentials_file(&malformed).is_err());
+    assert!(ServiceAccountSigner::from_credentials_file(dir.join("missing.json")).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_emulator_signer() {
+    let signer = EmulatorSigner;
+
+    assert_eq!(signer.algorithm(), "none");
+    assert!(signer.sign(b"hello").await.unwrap().is_empty());
+}
+
+fn iam_signer(server: &TestServer, account_id: Option<&str>) -> IamSigner<ReqwestApiClient> {
+    let client = ReqwestApiClient::new(
+        reqwest::Client::new(),
+        EmulatorCredentials::default().into(),
+    );
+
+    IamSigner {
+        client,
+        account_id: OnceCell::new_with(account_id.map(String::from)),
+        metadata: Some(GceMetadataClient::new(
+            reqwest::Client::new(),
+            server.url.trim_start_matches("http://").into(),
+        )),
+        iam_uri_root: server.url.clone(),
+    }
+}
+
+#[tokio::test]
+async fn test_iam_signer() {
+    let server = TestServer::start(|_| {
+        StubResponse::json(format!(
+            r#"{{"keyId": "key1", "signedBlob": "{}"}}"#,
+            STANDARD.encode(b"signature")
+        ))
+    })
+    .await;
+
+    let signer = iam_signer(&server, Some("sa@example.iam.gserviceaccount.com"));
+    let signature = signer.sign(b"hello").await.unwrap();
+    assert_eq!(signature, b"signature");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(
+        requests[0].path,
+        "/v1/projects/-/serviceAccounts/sa%40example.iam.gserviceaccount.com:signBlob"
+    );
+    assert!(
+        requests[0]
+            .headers
+            .contains(&("authorization".into(), "Bearer owner".into()))
+    );
+
+    let body: Value = from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["payload"], STANDARD.encode(b"hello"));
+}
+
+#[tokio::test]
+async fn test_iam_signer_resolves_account_from_metadata_server() {
+    let server = TestServer::start(|req| match req.path.as_str() {
+        "/computeMetadata/v1/instance/service-accounts/default/email" => StubResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: "default@example.iam.gserviceaccount.com".into(),
+        },
+        _ => StubResponse::json(format!(
+            r#"{{"keyId": "key1", "signedBlob": "{}"}}"#,
+            STANDARD.encode(b"signature")
+        )),
+    })
+    .await;
+
+    let signer = iam_signer(&server, None);
+    signer.sign(b"hello").await.unwrap();
+    signer.sign(b"world").await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3, "Account should be resolved only once");
+    assert!(
+        requests[0]
+            .headers
+            .contains(&("metadata-flavor".into(), "Google".into()))
+    );
+    assert_eq!(
+        requests[2].path,
+        "/v1/projects/-/serviceAccounts/default%40example.iam.gserviceaccount.com:signBlob"
+    );
+}
+
+#[tokio::test]
+async fn test_iam_signer_error() {
+    let server = TestServer::start(|_| StubResponse {
+        status: 403,
+        headers: vec![("content-type".into(), "application/json".into())],
+        body: r#"{"error": {"code": 403, "message": "PERMISSION_DENIED", "errors": []}}"#.into(),
+    })
+    .await;
+
+    let signer = iam_signer(&server, Some("sa@example.iam.gserviceaccount.com"));
+    let err = signer.sign(b"hello").await.unwrap_err();
+
+    assert!(matches!(err.current_context(), SignerError::FailedToSign));
+}
+
+struct StaticMetadata(&'static str);
+
+impl MetadataClient for StaticMetadata {
+    async fn service_account_email(&self) -> Result<String, Report<SignerError>> {
+        Ok(self.0.into())
+    }
+}
+
+#[tokio::test]
+async fn test_iam_signer_with_metadata_client() {
+    let server = TestServer::start(|_| {
+        StubResponse::json(format!(
+            r#"{{"keyId": "key1", "signedBlob": "{}"}}"#,
+            STANDARD.encode(b"signature")
+        ))
+    })
+    .await;
+
+    let signer = IamSigner {
+        iam_uri_root: server.url.clone(),
+        ..IamSigner::from_metadata_server(
+            ReqwestApiClient::new(
+                reqwest::Client::new(),
+                EmulatorCredentials::default().into(),
+            ),
+            StaticMetadata("custom@example.iam.gserviceaccount.com"),
+        )
+    };
+
+    assert_eq!(
+        signer.account_id().await.unwrap(),
+        "custom@example.iam.gserviceaccount.com"
+    );
+    signer.sign(b"hello").await.unwrap();
+    assert_eq!(
+        server.requests()[0].path,
+        "/v1/projects/-/serviceAccounts/custom%40example.iam.gserviceaccount.com:signBlob"
+    );
+}
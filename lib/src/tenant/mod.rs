@@ -0,0 +1,350 @@
+//! Identity Platform tenant management interface
+
+#[cfg(test)]
+mod test;
+
+use crate::api_uri::{ApiUriBuilder, TenantManagementRestApi};
+use crate::auth::FIREBASE_AUTH_REST_AUTHORITY;
+use crate::client::ApiHttpClient;
+use crate::client::error::ApiClientError;
+use crate::util::update_mask::{MaskedUpdate, non_empty_update_mask};
+use crate::validation::{ValidationError, is_tenant_id};
+use error_stack::Report;
+use http::Method;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MultiFactorState {
+    StateUnspecified,
+    Enabled,
+    Disabled,
+    /// Second factor is required, only available for project configuration
+    Mandatory,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MultiFactorProvider {
+    PhoneSms,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiFactorConfig {
+    pub state: MultiFactorState,
+    #[serde(default)]
+    pub enabled_providers: Vec<MultiFactorProvider>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Tenant {
+    /// Resource name, `projects/{project_id}/tenants/{tenant_id}`
+    pub name: String,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub allow_password_signup: bool,
+    #[serde(default)]
+    pub enable_email_link_signin: bool,
+    #[serde(default)]
+    pub enable_anonymous_user: bool,
+    #[serde(default)]
+    pub disable_auth: bool,
+    pub mfa_config: Option<MultiFactorConfig>,
+    /// Phone numbers mapped to their fixed verification codes
+    #[serde(default)]
+    pub test_phone_numbers: BTreeMap<String, String>,
+}
+
+impl Tenant {
+    pub fn tenant_id(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantList {
+    #[serde(default)]
+    pub tenants: Vec<Tenant>,
+    pub next_page_token: Option<String>,
+}
+
+/// Tenant settings for creating a tenant or updating set fields of one
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_password_signup: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_email_link_signin: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_anonymous_user: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_auth: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfa_config: Option<MultiFactorConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_phone_numbers: Option<BTreeMap<String, String>>,
+}
+
+impl TenantConfig {
+    pub fn builder() -> TenantConfigBuilder {
+        TenantConfigBuilder::default()
+    }
+}
+
+impl MaskedUpdate for TenantConfig {
+    const ATOMIC_FIELDS: &'static [&'static str] = &["testPhoneNumbers"];
+}
+
+#[derive(Default)]
+pub struct TenantConfigBuilder {
+    config: TenantConfig,
+}
+
+impl TenantConfigBuilder {
+    pub fn display_name(mut self, value: String) -> Self {
+        self.config.display_name = Some(value);
+
+        self
+    }
+
+    /// Allow email and password sign in
+    pub fn allow_password_signup(mut self, value: bool) -> Self {
+        self.config.allow_password_signup = Some(value);
+
+        self
+    }
+
+    /// Allow passwordless email link sign in
+    pub fn enable_email_link_signin(mut self, value: bool) -> Self {
+        self.config.enable_email_link_signin = Some(value);
+
+        self
+    }
+
+    pub fn enable_anonymous_user(mut self, value: bool) -> Self {
+        self.config.enable_anonymous_user = Some(value);
+
+        self
+    }
+
+    /// Disable sign in of all users of the tenant
+    pub fn disable_auth(mut self, value: bool) -> Self {
+        self.config.disable_auth = Some(value);
+
+        self
+    }
+
+    pub fn mfa_config(mut self, value: MultiFactorConfig) -> Self {
+        self.config.mfa_config = Some(value);
+
+        self
+    }
+
+    /// Replace all test phone numbers with given numbers and their verification codes
+    pub fn test_phone_numbers(mut self, value: BTreeMap<String, String>) -> Self {
+        self.config.test_phone_numbers = Some(value);
+
+        self
+    }
+
+    pub fn build(self) -> TenantConfig {
+        self.config
+    }
+}
+
+/// URI of a single tenant, IDs are validated as they become part of the path
+fn tenant_uri(
+    uri_builder: &ApiUriBuilder,
+    tenant_id: &str,
+) -> Result<String, Report<ApiClientError>> {
+    if !is_tenant_id(tenant_id) {
+        return Err(Report::new(ApiClientError::Validation(
+            ValidationError::InvalidTenantId,
+        )));
+    }
+
+    Ok(uri_builder.build(TenantManagementRestApi::Tenants) + "/" + tenant_id)
+}
+
+pub trait TenantManagementService<ApiHttpClientT>: Send + Sync + 'static
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    fn get_client(&self) -> &ApiHttpClientT;
+    fn get_tenant_uri_builder(&self) -> &ApiUriBuilder;
+
+    /// Create a new tenant
+    /// # Example
+    /// ```rust
+    /// let tenant = tenants.create_tenant(
+    ///     TenantConfig::builder()
+    ///         .display_name("customer-a".into())
+    ///         .allow_password_signup(true)
+    ///         .build()
+    /// ).await.unwrap();
+    /// ```
+    fn create_tenant(
+        &self,
+        config: TenantConfig,
+    ) -> impl Future<Output = Result<Tenant, Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_tenant_uri_builder();
+
+            client
+                .send_request_body(
+                    uri_builder.build(TenantManagementRestApi::Tenants),
+                    Method::POST,
+                    config,
+                )
+                .await
+        }
+    }
+
+    /// Get tenant with given ID
+    fn get_tenant(
+        &self,
+        tenant_id: String,
+    ) -> impl Future<Output = Result<Tenant, Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_tenant_uri_builder();
+
+            client
+                .send_request(tenant_uri(uri_builder, &tenant_id)?, Method::GET)
+                .await
+        }
+    }
+
+    /// Update fields of a tenant which are set in `config`, leaving others unchanged
+    fn update_tenant(
+        &self,
+        tenant_id: String,
+        config: TenantConfig,
+    ) -> impl Future<Output = Result<Tenant, Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_tenant_uri_builder();
+            let mask = non_empty_update_mask(&config)?;
+
+            client
+                .send_request_body(
+                    tenant_uri(uri_builder, &tenant_id)?
+                        + "?updateMask="
+                        + &urlencoding::encode(&mask),
+                    Method::PATCH,
+                    config,
+                )
+                .await
+        }
+    }
+
+    /// Delete tenant with given ID along with all of its users
+    fn delete_tenant(
+        &self,
+        tenant_id: String,
+    ) -> impl Future<Output = Result<(), Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_tenant_uri_builder();
+
+            let _: IgnoredAny = client
+                .send_request(tenant_uri(uri_builder, &tenant_id)?, Method::DELETE)
+                .await?;
+
+            Ok(())
+        }
+    }
+
+    /// List tenants page by page, pass previous page to get the next one
+    /// # Example
+    /// ```rust
+    /// let mut page: Option<TenantList> = None;
+    /// while let Some(tenants) = tenant_manager.list_tenants(100, page).await.unwrap() {
+    ///     // Process tenants.tenants
+    ///     page = Some(tenants);
+    /// }
+    /// ```
+    fn list_tenants(
+        &self,
+        tenants_per_page: usize,
+        prev: Option<TenantList>,
+    ) -> impl Future<Output = Result<Option<TenantList>, Report<ApiClientError>>> + Send {
+        async move {
+            let client = self.get_client();
+            let uri_builder = self.get_tenant_uri_builder();
+            let mut params = vec![("pageSize".to_string(), tenants_per_page.to_string())];
+
+            if let Some(prev) = prev {
+                if let Some(next_page_token) = prev.next_page_token {
+                    params.push(("pageToken".to_string(), next_page_token));
+                } else {
+                    return Ok(None);
+                }
+            }
+
+            let tenants: TenantList = client
+                .send_request_with_params(
+                    uri_builder.build(TenantManagementRestApi::Tenants),
+                    params.into_iter(),
+                    Method::GET,
+                )
+                .await?;
+
+            Ok(Some(tenants))
+        }
+    }
+}
+
+pub struct TenantManager<ApiHttpClientT> {
+    client: ApiHttpClientT,
+    tenant_uri_builder: ApiUriBuilder,
+}
+
+impl<ApiHttpClientT> TenantManager<ApiHttpClientT>
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    /// Create tenant manager for emulator
+    pub fn emulated(emulator_url: String, project_id: &str, client: ApiHttpClientT) -> Self {
+        let root =
+            emulator_url + &format!("/{FIREBASE_AUTH_REST_AUTHORITY}/v2/projects/{project_id}");
+
+        Self {
+            client,
+            tenant_uri_builder: ApiUriBuilder::new(root),
+        }
+    }
+
+    /// Create tenant manager for live project
+    pub fn live(project_id: &str, client: ApiHttpClientT) -> Self {
+        let root = format!("https://{FIREBASE_AUTH_REST_AUTHORITY}/v2/projects/{project_id}");
+
+        Self {
+            client,
+            tenant_uri_builder: ApiUriBuilder::new(root),
+        }
+    }
+}
+
+impl<ApiHttpClientT> TenantManagementService<ApiHttpClientT> for TenantManager<ApiHttpClientT>
+where
+    ApiHttpClientT: ApiHttpClient + Send + Sync,
+{
+    fn get_client(&self) -> &ApiHttpClientT {
+        &self.client
+    }
+
+    fn get_tenant_uri_builder(&self) -> &ApiUriBuilder {
+        &self.tenant_uri_builder
+    }
+}
@@ -0,0 +1,178 @@
+use super::{
+    MultiFactorConfig, MultiFactorProvider, MultiFactorState, TenantConfig, TenantList,
+    TenantManagementService,
+};
+use crate::App;
+use crate::client::error::ApiClientError;
+use crate::util::test_server::TestServer;
+use crate::validation::ValidationError;
+use serde_json::{Value, from_slice, json};
+use std::collections::BTreeMap;
+
+const TENANTS_PATH: &str =
+    "/identitytoolkit.googleapis.com/v2/projects/demo-firebase-project/tenants";
+
+fn tenant_json(tenant_id: &str) -> Value {
+    json!({
+        "name": format!("projects/demo-firebase-project/tenants/{tenant_id}"),
+        "displayName": "customer-a",
+        "allowPasswordSignup": true,
+        "mfaConfig": {"state": "ENABLED", "enabledProviders": ["PHONE_SMS"]},
+        "testPhoneNumbers": {"+11234567890": "123456"}
+    })
+}
+
+#[tokio::test]
+async fn test_create_tenant() {
+    let (server, tenants) = TestServer::json_stub_client(tenant_json("customer-a-1x2y"), |url| {
+        App::emulated().tenant_manager(url)
+    })
+    .await;
+
+    let tenant = tenants
+        .create_tenant(
+            TenantConfig::builder()
+                .display_name("customer-a".into())
+                .allow_password_signup(true)
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(tenant.tenant_id(), "customer-a-1x2y");
+    assert!(tenant.allow_password_signup);
+    assert!(!tenant.enable_anonymous_user);
+    assert_eq!(
+        tenant.mfa_config.unwrap().enabled_providers,
+        vec![MultiFactorProvider::PhoneSms]
+    );
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, TENANTS_PATH);
+    assert_eq!(
+        from_slice::<Value>(&request.body).unwrap(),
+        json!({"displayName": "customer-a", "allowPasswordSignup": true})
+    );
+}
+
+#[tokio::test]
+async fn test_get_and_delete_tenant() {
+    let (server, tenants) = TestServer::json_stub_client(tenant_json("tenant-1"), |url| {
+        App::emulated().tenant_manager(url)
+    })
+    .await;
+
+    let tenant = tenants.get_tenant("tenant-1".into()).await.unwrap();
+    assert_eq!(tenant.display_name.as_deref(), Some("customer-a"));
+    assert_eq!(tenant.test_phone_numbers["+11234567890"], "123456");
+
+    tenants.delete_tenant("tenant-1".into()).await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, format!("{TENANTS_PATH}/tenant-1"));
+    assert_eq!(requests[1].method, "DELETE");
+    assert_eq!(requests[1].path, format!("{TENANTS_PATH}/tenant-1"));
+}
+
+#[tokio::test]
+async fn test_update_tenant() {
+    let (server, tenants) = TestServer::json_stub_client(tenant_json("tenant-1"), |url| {
+        App::emulated().tenant_manager(url)
+    })
+    .await;
+
+    tenants
+        .update_tenant(
+            "tenant-1".into(),
+            TenantConfig::builder()
+                .display_name("customer-b".into())
+                .mfa_config(MultiFactorConfig {
+                    state: MultiFactorState::Disabled,
+                    enabled_providers: Vec::new(),
+                })
+                .test_phone_numbers(BTreeMap::from([("+11234567890".into(), "654321".into())]))
+                .build(),
+        )
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "PATCH");
+    assert_eq!(
+        request.path,
+        format!(
+            "{TENANTS_PATH}/tenant-1?updateMask=displayName%2CmfaConfig.enabledProviders%2CmfaConfig.state%2CtestPhoneNumbers"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_update_tenant_without_fields() {
+    let (server, tenants) = TestServer::json_stub_client(tenant_json("tenant-1"), |url| {
+        App::emulated().tenant_manager(url)
+    })
+    .await;
+
+    let err = tenants
+        .update_tenant("tenant-1".into(), TenantConfig::default())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err.current_context(), ApiClientError::EmptyUpdate));
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_tenant_id_rejected() {
+    let (server, tenants) = TestServer::json_stub_client(tenant_json("tenant-1"), |url| {
+        App::emulated().tenant_manager(url)
+    })
+    .await;
+
+    let err = tenants.get_tenant("../tenant-1".into()).await.unwrap_err();
+
+    assert!(matches!(
+        err.current_context(),
+        ApiClientError::Validation(ValidationError::InvalidTenantId)
+    ));
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_list_tenants() {
+    let (server, tenants) = TestServer::json_stub_client(
+        json!({
+            "tenants": [tenant_json("tenant-1"), tenant_json("tenant-2")],
+            "nextPageToken": "page-2"
+        }),
+        |url| App::emulated().tenant_manager(url),
+    )
+    .await;
+
+    let page = tenants.list_tenants(2, None).await.unwrap().unwrap();
+    assert_eq!(page.tenants.len(), 2);
+    assert_eq!(page.tenants[1].tenant_id(), "tenant-2");
+
+    tenants.list_tenants(2, Some(page)).await.unwrap();
+    let last_page = TenantList {
+        tenants: Vec::new(),
+        next_page_token: None,
+    };
+    assert!(
+        tenants
+            .list_tenants(2, Some(last_page))
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].path, format!("{TENANTS_PATH}?pageSize=2"));
+    assert_eq!(
+        requests[1].path,
+        format!("{TENANTS_PATH}?pageSize=2&pageToken=page-2")
+    );
+}
@@ -0,0 +1,254 @@
+//! Offline issuing of Firebase tokens for tests of services relying on token verification
+
+#[cfg(test)]
+mod test;
+
+use crate::auth::Claims;
+use crate::jwt::{GOOGLE_COOKIE_ISSUER_PREFIX, GOOGLE_ID_TOKEN_ISSUER_PREFIX, LiveValidator};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, encode};
+use rand_core::OsRng;
+use rsa::RsaPrivateKey;
+use rsa::pkcs1::{EncodeRsaPrivateKey, LineEnding};
+use rsa::traits::PublicKeyParts;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+
+/// Key ID of every issuer, so tokens of another issuer fail on signature rather than key lookup
+pub const TEST_KEY_ID: &str = "firebase-admin-sdk-test-key";
+const TEST_KEY_BITS: usize = 2048;
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::hours(1);
+
+#[derive(Debug, Clone, Copy)]
+enum TokenKind {
+    IdToken,
+    SessionCookie,
+}
+
+impl TokenKind {
+    fn issuer_prefix(self) -> &'static str {
+        match self {
+            Self::IdToken => GOOGLE_ID_TOKEN_ISSUER_PREFIX,
+            Self::SessionCookie => GOOGLE_COOKIE_ISSUER_PREFIX,
+        }
+    }
+}
+
+/// Mints ID tokens and session cookies signed with a freshly generated RSA key,
+/// accepted only by validators built from the same issuer.
+/// # Example
+/// ```rust
+/// let issuer = TestTokenIssuer::new("demo-project".into());
+/// let validator = issuer.jwt_validator();
+///
+/// let token = issuer.id_token("some-uid".into()).with_tenant("tenant-1".into()).sign();
+/// let decoded = validator.validate(&token).await.unwrap();
+/// ```
+pub struct TestTokenIssuer {
+    project_id: String,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl TestTokenIssuer {
+    /// Create issuer for given project with a new RSA keypair
+    pub fn new(project_id: String) -> Self {
+        // generating a key only fails for unsupported sizes
+        let private_key = RsaPrivateKey::new(&mut OsRng, TEST_KEY_BITS).unwrap();
+        let pem = private_key.to_pkcs1_pem(LineEnding::LF).unwrap();
+        let public_key = private_key.to_public_key();
+
+        Self {
+            project_id,
+            encoding_key: EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap(),
+            decoding_key: DecodingKey::from_rsa_raw_components(
+                &public_key.n().to_bytes_be(),
+                &public_key.e().to_bytes_be(),
+            ),
+        }
+    }
+
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    /// Start building an ID token for given user
+    pub fn id_token(&self, uid: String) -> TestTokenBuilder<'_> {
+        TestTokenBuilder::new(self, TokenKind::IdToken, uid)
+    }
+
+    /// Start building a session cookie for given user
+    pub fn session_cookie(&self, uid: String) -> TestTokenBuilder<'_> {
+        TestTokenBuilder::new(self, TokenKind::SessionCookie, uid)
+    }
+
+    /// Validator of ID tokens trusting only the key of this issuer
+    pub fn jwt_validator(&self) -> LiveValidator {
+        self.validator(TokenKind::IdToken)
+    }
+
+    /// Validator of session cookies trusting only the key of this issuer
+    pub fn cookie_validator(&self) -> LiveValidator {
+        self.validator(TokenKind::SessionCookie)
+    }
+
+    fn validator(&self, kind: TokenKind) -> LiveValidator {
+        let keys = HashMap::from([(TEST_KEY_ID.to_string(), self.decoding_key.clone())]);
+
+        LiveValidator::with_static_keys(
+            self.project_id.clone(),
+            kind.issuer_prefix(),
+            Arc::new(keys),
+        )
+    }
+}
+
+/// Token being minted by [`TestTokenIssuer`], defaults to a token
+/// issued now, valid for an hour and signed in with password
+pub struct TestTokenBuilder<'a> {
+    issuer: &'a TestTokenIssuer,
+    kind: TokenKind,
+    uid: String,
+    key_id: String,
+    issued_at: OffsetDateTime,
+    auth_time: Option<OffsetDateTime>,
+    expires_at: Option<OffsetDateTime>,
+    audience: Option<String>,
+    email: Option<String>,
+    tenant: Option<String>,
+    sign_in_provider: String,
+    claims: Map<String, Value>,
+}
+
+impl<'a> TestTokenBuilder<'a> {
+    fn new(issuer: &'a TestTokenIssuer, kind: TokenKind, uid: String) -> Self {
+        Self {
+            issuer,
+            kind,
+            uid,
+            key_id: TEST_KEY_ID.into(),
+            issued_at: OffsetDateTime::now_utc(),
+            auth_time: None,
+            expires_at: None,
+            audience: None,
+            email: None,
+            tenant: None,
+            sign_in_provider: "password".into(),
+            claims: Map::new(),
+        }
+    }
+
+    pub fn with_issued_at(mut self, issued_at: OffsetDateTime) -> Self {
+        self.issued_at = issued_at;
+
+        self
+    }
+
+    /// Defaults to time of issuing
+    pub fn with_auth_time(mut self, auth_time: OffsetDateTime) -> Self {
+        self.auth_time = Some(auth_time);
+
+        self
+    }
+
+    /// Defaults to an hour after time of issuing
+    pub fn with_expires_at(mut self, expires_at: OffsetDateTime) -> Self {
+        self.expires_at = Some(expires_at);
+
+        self
+    }
+
+    /// Override audience, which otherwise is the issuer's project ID
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+
+        self
+    }
+
+    /// Override key ID in token header
+    pub fn with_key_id(mut self, key_id: String) -> Self {
+        self.key_id = key_id;
+
+        self
+    }
+
+    pub fn with_email(mut self, email: String) -> Self {
+        self.email = Some(email);
+
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant: String) -> Self {
+        self.tenant = Some(tenant);
+
+        self
+    }
+
+    pub fn with_sign_in_provider(mut self, provider: String) -> Self {
+        self.sign_in_provider = provider;
+
+        self
+    }
+
+    /// Add a custom claim to the token
+    pub fn with_claim(mut self, name: String, value: Value) -> Self {
+        self.claims.insert(name, value);
+
+        self
+    }
+
+    /// Add custom claims to the token
+    pub fn with_claims(mut self, claims: Claims) -> Self {
+        self.claims
+            .extend(claims.get().iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        self
+    }
+
+    /// Sign the token with the issuer's key
+    pub fn sign(self) -> String {
+        let mut identities = Map::new();
+        if let Some(email) = &self.email {
+            identities.insert("email".into(), json!([email]));
+        }
+
+        let mut firebase = json!({
+            "identities": identities,
+            "sign_in_provider": self.sign_in_provider,
+        });
+        if let Some(tenant) = self.tenant {
+            firebase["tenant"] = tenant.into();
+        }
+
+        let mut payload = self.claims;
+        payload.extend(
+            json!({
+                "iss": format!("{}{}", self.kind.issuer_prefix(), self.issuer.project_id),
+                "aud": self.audience.unwrap_or_else(|| self.issuer.project_id.clone()),
+                "sub": self.uid,
+                "user_id": self.uid,
+                "iat": self.issued_at.unix_timestamp(),
+                "auth_time": self.auth_time.unwrap_or(self.issued_at).unix_timestamp(),
+                "exp": self
+                    .expires_at
+                    .unwrap_or(self.issued_at + DEFAULT_TOKEN_LIFETIME)
+                    .unix_timestamp(),
+                "firebase": firebase,
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        if let Some(email) = self.email {
+            payload.insert("email".into(), email.into());
+            payload.insert("email_verified".into(), true.into());
+        }
+
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(self.key_id);
+
+        // encoding a JSON object with a valid RSA key does not fail
+        encode(&header, &payload, &self.issuer.encoding_key).unwrap()
+    }
+}
@@ -0,0 +1,87 @@
+use super::TestTokenIssuer;
+use crate::jwt::{TokenValidator, TokenVerificationError};
+use serde_json::json;
+use std::sync::LazyLock;
+use time::{Duration, OffsetDateTime};
+
+static ISSUER: LazyLock<TestTokenIssuer> =
+    LazyLock::new(|| TestTokenIssuer::new("demo-firebase-project".into()));
+
+async fn assert_id_token_rejected(token: &str, expected: TokenVerificationError) {
+    let err = ISSUER.jwt_validator().validate(token).await.unwrap_err();
+
+    assert_eq!(
+        std::mem::discriminant(err.current_context()),
+        std::mem::discriminant(&expected),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_id_token_verified() {
+    let token = ISSUER
+        .id_token("test-uid".into())
+        .with_email("test@example.com".into())
+        .with_tenant("tenant-1".into())
+        .with_sign_in_provider("google.com".into())
+        .with_claim("role".into(), json!("admin"))
+        .sign();
+
+    let decoded = ISSUER.jwt_validator().validate(&token).await.unwrap();
+
+    assert_eq!(decoded.uid, "test-uid");
+    assert_eq!(decoded.audience, "demo-firebase-project");
+    assert_eq!(decoded.email.as_deref(), Some("test@example.com"));
+    assert_eq!(decoded.firebase.tenant.as_deref(), Some("tenant-1"));
+    assert_eq!(decoded.firebase.sign_in_provider, "google.com");
+    assert_eq!(decoded.claims.get()["role"], json!("admin"));
+}
+
+#[tokio::test]
+async fn test_session_cookie_verified() {
+    let cookie = ISSUER.session_cookie("test-uid".into()).sign();
+
+    let decoded = ISSUER.cookie_validator().validate(&cookie).await.unwrap();
+    assert_eq!(decoded.uid, "test-uid");
+
+    assert_id_token_rejected(&cookie, TokenVerificationError::WrongIssuer).await;
+}
+
+#[tokio::test]
+async fn test_expired_token_rejected() {
+    let issued_at = OffsetDateTime::now_utc() - Duration::hours(3);
+    let token = ISSUER
+        .id_token("test-uid".into())
+        .with_issued_at(issued_at)
+        .sign();
+
+    assert_id_token_rejected(&token, TokenVerificationError::Expired).await;
+}
+
+#[tokio::test]
+async fn test_wrong_audience_rejected() {
+    let token = ISSUER
+        .id_token("test-uid".into())
+        .with_audience("other-project".into())
+        .sign();
+
+    assert_id_token_rejected(&token, TokenVerificationError::WrongAudience).await;
+}
+
+#[tokio::test]
+async fn test_foreign_signature_rejected() {
+    let other_issuer = TestTokenIssuer::new("demo-firebase-project".into());
+    let token = other_issuer.id_token("test-uid".into()).sign();
+
+    assert_id_token_rejected(&token, TokenVerificationError::InvalidSignature).await;
+}
+
+#[tokio::test]
+async fn test_unknown_key_rejected() {
+    let token = ISSUER
+        .id_token("test-uid".into())
+        .with_key_id("unknown".into())
+        .sign();
+
+    assert_id_token_rejected(&token, TokenVerificationError::MissingKey).await;
+}
@@ -0,0 +1,15 @@
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::{DecodeError, Engine};
+
+const BASE64_CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const BASE64_STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, BASE64_CONFIG);
+const BASE64_URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, BASE64_CONFIG);
+
+/// Decode base64 in either standard or URL safe alphabet, with or without padding
+pub(crate) fn decode_base64(value: &str) -> Result<Vec<u8>, DecodeError> {
+    BASE64_STANDARD
+        .decode(value)
+        .or_else(|_| BASE64_URL_SAFE.decode(value))
+}
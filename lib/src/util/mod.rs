@@ -1,7 +1,12 @@
 //! Utilities
 
+pub(crate) mod encoding;
+pub(crate) mod prefetch;
 pub mod serialize;
 #[cfg(test)]
 mod test;
+#[cfg(test)]
+pub(crate) mod test_server;
+pub(crate) mod update_mask;
 
 pub use serialize::{I128EpochMs, StrEpochMs, StrEpochSec};
@@ -0,0 +1,77 @@
+use futures::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Flattens a stream of pages, polling it on every poll of the consumer
+/// to fetch up to `capacity` pages ahead of the one being drained
+pub(crate) struct Prefetch<S, I: IntoIterator, E> {
+    inner: Pin<Box<S>>,
+    buffer: VecDeque<Result<I, E>>,
+    current: Option<I::IntoIter>,
+    capacity: usize,
+    done: bool,
+}
+
+impl<S, I, E> Prefetch<S, I, E>
+where
+    S: Stream<Item = Result<I, E>>,
+    I: IntoIterator,
+{
+    pub fn new(inner: S, capacity: usize) -> Self {
+        Self {
+            inner: Box::pin(inner),
+            buffer: VecDeque::with_capacity(capacity),
+            current: None,
+            capacity,
+            done: false,
+        }
+    }
+}
+
+// Inner stream is boxed and buffered pages are never pinned
+impl<S, I: IntoIterator, E> Unpin for Prefetch<S, I, E> {}
+
+impl<S, I, E> Stream for Prefetch<S, I, E>
+where
+    S: Stream<Item = Result<I, E>>,
+    I: IntoIterator,
+{
+    type Item = Result<I::Item, E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        // Inner stream is not polled again once pending within the same poll
+        let mut pending = false;
+        loop {
+            while !this.done && !pending && this.buffer.len() < this.capacity {
+                match this.inner.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(page)) => this.buffer.push_back(page),
+                    Poll::Ready(None) => this.done = true,
+                    Poll::Pending => {
+                        pending = true;
+                        break;
+                    }
+                }
+            }
+
+            if let Some(item) = this.current.as_mut().and_then(Iterator::next) {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            this.current = None;
+
+            match this.buffer.pop_front() {
+                Some(Ok(page)) => this.current = Some(page.into_iter()),
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None if this.done => return Poll::Ready(None),
+                None if pending => return Poll::Pending,
+                None => match this.inner.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(page)) => this.buffer.push_back(page),
+                    Poll::Ready(None) => this.done = true,
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use time::OffsetDateTime;
 
@@ -50,6 +51,30 @@ impl<'de> de::Deserialize<'de> for StrEpochMs {
     }
 }
 
+impl Serialize for StrEpochMs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let unix_ts_ms = self.dt.unix_timestamp_nanos() / 1000000;
+        serializer.serialize_str(&unix_ts_ms.to_string())
+    }
+}
+
+/// Serialize optional timestamp as a string containing count of miliseconds since UNIX epoch
+pub(crate) fn serialize_opt_str_epoch_ms<S>(
+    value: &Option<OffsetDateTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(dt) => StrEpochMs::from(*dt).serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StrEpochSec {
     dt: OffsetDateTime,
@@ -98,6 +123,29 @@ impl<'de> de::Deserialize<'de> for StrEpochSec {
     }
 }
 
+impl Serialize for StrEpochSec {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.dt.unix_timestamp().to_string())
+    }
+}
+
+/// Serialize optional timestamp as a string containing count of seconds since UNIX epoch
+pub(crate) fn serialize_opt_str_epoch_sec<S>(
+    value: &Option<OffsetDateTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(dt) => StrEpochSec::from(*dt).serialize(serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct I128EpochMs {
     dt: OffsetDateTime,
@@ -124,6 +172,20 @@ impl Visitor<'_> for I128EpochMsVisitor {
         formatter.write_str("an integer containing count of miliseconds since UNIX epoch")
     }
 
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_i128(value.into())
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_i128(value.into())
+    }
+
     fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E>
     where
         E: de::Error,
@@ -140,6 +202,36 @@ impl<'de> de::Deserialize<'de> for I128EpochMs {
     where
         D: de::Deserializer<'de>,
     {
-        deserializer.deserialize_i128(I128EpochMsVisitor)
+        deserializer.deserialize_any(I128EpochMsVisitor)
     }
 }
+
+/// Deserialize optional string containing count of miliseconds since UNIX epoch
+pub(crate) fn deserialize_opt_str_epoch_ms<'de, D>(
+    deserializer: D,
+) -> Result<Option<OffsetDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<StrEpochMs>::deserialize(deserializer)?.map(Into::into))
+}
+
+/// Deserialize optional string containing count of seconds since UNIX epoch
+pub(crate) fn deserialize_opt_str_epoch_sec<'de, D>(
+    deserializer: D,
+) -> Result<Option<OffsetDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<StrEpochSec>::deserialize(deserializer)?.map(Into::into))
+}
+
+/// Deserialize optional integer containing count of miliseconds since UNIX epoch
+pub(crate) fn deserialize_opt_i128_epoch_ms<'de, D>(
+    deserializer: D,
+) -> Result<Option<OffsetDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<I128EpochMs>::deserialize(deserializer)?.map(Into::into))
+}
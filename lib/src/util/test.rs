@@ -1,5 +1,6 @@
+use super::update_mask::update_mask;
 use super::{I128EpochMs, StrEpochMs, StrEpochSec};
-use serde_json::from_str;
+use serde_json::{from_str, to_string};
 use time::{Month, OffsetDateTime};
 
 #[test]
@@ -43,3 +44,80 @@ fn test_int_epoch_ms() {
     assert_eq!(off_dt.second(), 1);
     assert_eq!(off_dt.millisecond(), 1);
 }
+
+#[test]
+fn test_str_epoch_sec_serialize() {
+    let dt: StrEpochSec = OffsetDateTime::from_unix_timestamp(1001).unwrap().into();
+
+    assert_eq!(to_string(&dt).unwrap(), "\"1001\"");
+}
+
+#[test]
+fn test_update_mask() {
+    let update = serde_json::json!({
+        "displayName": "Name",
+        "unset": null,
+        "mfaConfig": {"state": "ENABLED", "providers": ["PHONE_SMS"]},
+        "testPhoneNumbers": {"+11234567890": "123456"},
+    });
+
+    let mask = update_mask(&update, &["testPhoneNumbers"]).unwrap();
+
+    assert_eq!(
+        mask,
+        "displayName,mfaConfig.providers,mfaConfig.state,testPhoneNumbers"
+    );
+}
+
+#[tokio::test]
+async fn test_prefetch_keeps_order_and_reads_ahead() {
+    use super::prefetch::Prefetch;
+    use futures::{StreamExt, TryStreamExt, stream};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let produced = Arc::new(AtomicUsize::new(0));
+    let counter = produced.clone();
+    let pages = stream::unfold(0, move |page| {
+        let counter = counter.clone();
+        async move {
+            if page == 5 {
+                return None;
+            }
+            // Every page takes a round trip to the executor, like a network request
+            tokio::task::yield_now().await;
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            Some((
+                Ok::<_, ()>(vec![page * 3, page * 3 + 1, page * 3 + 2]),
+                page + 1,
+            ))
+        }
+    });
+    let mut prefetch = Prefetch::new(pages, 2);
+
+    assert_eq!(prefetch.next().await, Some(Ok(0)));
+    assert_eq!(produced.load(Ordering::SeqCst), 1);
+    assert_eq!(prefetch.next().await, Some(Ok(1)));
+    assert_eq!(prefetch.next().await, Some(Ok(2)));
+    assert_eq!(
+        produced.load(Ordering::SeqCst),
+        3,
+        "Pages should be fetched while the current one is drained"
+    );
+    assert_eq!(
+        prefetch.try_collect::<Vec<_>>().await,
+        Ok((3..15).collect::<Vec<_>>())
+    );
+}
+
+#[tokio::test]
+async fn test_prefetch_yields_page_errors() {
+    use super::prefetch::Prefetch;
+    use futures::{StreamExt, stream};
+
+    let pages = stream::iter(vec![Ok(vec![1, 2]), Err("failed"), Ok(vec![3])]);
+    let items: Vec<_> = Prefetch::new(pages, 0).collect().await;
+
+    assert_eq!(items, vec![Ok(1), Ok(2), Err("failed"), Ok(3)]);
+}
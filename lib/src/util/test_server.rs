@@ -0,0 +1,143 @@
+//! Minimal local HTTP server standing in for Google APIs in tests
+
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+#[derive(Debug, Clone)]
+pub(crate) struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+pub(crate) struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn json(body: String) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body,
+        }
+    }
+}
+
+pub(crate) struct TestServer {
+    pub url: String,
+    pub requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl TestServer {
+    /// Serve every request with the response produced by `handler`
+    pub async fn start<H>(handler: H) -> Self
+    where
+        H: Fn(&RecordedRequest) -> StubResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::default();
+        let handler = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let recorded = recorded.clone();
+                let handler = handler.clone();
+
+                tokio::spawn(async move {
+                    let mut stream = BufReader::new(stream);
+
+                    while let Some(request) = read_request(&mut stream).await {
+                        let response = handler(&request);
+                        recorded.lock().unwrap().push(request);
+
+                        let mut head = format!(
+                            "HTTP/1.1 {} STUB\r\ncontent-length: {}\r\n",
+                            response.status,
+                            response.body.len()
+                        );
+                        for (name, value) in response.headers {
+                            head += &format!("{name}: {value}\r\n");
+                        }
+                        head += "\r\n";
+
+                        let stream = stream.get_mut();
+                        stream.write_all(head.as_bytes()).await.unwrap();
+                        stream.write_all(response.body.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Serve every request with the same JSON `body`
+    pub async fn json_stub(body: Value) -> Self {
+        let body = body.to_string();
+        Self::start(move |_| StubResponse::json(body.clone())).await
+    }
+
+    /// Serve every request with the same JSON `body`, along with a client of the server
+    /// built by `connect` from its URL, e.g. `|url| App::emulated().auth(url)`
+    pub async fn json_stub_client<T>(body: Value, connect: impl FnOnce(String) -> T) -> (Self, T) {
+        let server = Self::json_stub(body).await;
+        let client = connect(server.url.clone());
+
+        (server, client)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request<S>(stream: &mut BufReader<S>) -> Option<RecordedRequest>
+where
+    S: tokio::io::AsyncRead + Unpin,
+{
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await.ok()? == 0 {
+        return None;
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await.ok()?;
+
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
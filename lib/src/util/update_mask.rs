@@ -0,0 +1,62 @@
+//! Field masks for partial updates of Google API resources
+
+use crate::client::error::ApiClientError;
+use error_stack::{Report, ResultExt};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Comma separated camelCase paths of every field set in serialized `update`.
+/// Nested objects contribute paths of their own fields, unless listed in `atomic_fields`,
+/// e.g. maps which are always replaced as a whole.
+pub(crate) fn update_mask<T: Serialize>(
+    update: &T,
+    atomic_fields: &[&str],
+) -> Result<String, serde_json::Error> {
+    let mut paths = Vec::new();
+    if let Value::Object(fields) = serde_json::to_value(update)? {
+        collect_paths(&fields, "", atomic_fields, &mut paths);
+    }
+
+    Ok(paths.join(","))
+}
+
+/// Partial update of a resource, sent along with the mask of fields it sets
+pub(crate) trait MaskedUpdate: Serialize {
+    /// Nested objects replaced as a whole on update rather than field by field,
+    /// e.g. maps whose entries missing from the update get removed
+    const ATOMIC_FIELDS: &'static [&'static str] = &[];
+}
+
+/// Mask of fields set by `update`, rejecting updates that set no fields,
+/// which the API would otherwise apply as a full replacement with defaults
+pub(crate) fn non_empty_update_mask<T: MaskedUpdate>(
+    update: &T,
+) -> Result<String, Report<ApiClientError>> {
+    let mask = update_mask(update, T::ATOMIC_FIELDS)
+        .change_context(ApiClientError::FailedToSerializeRequest)?;
+    if mask.is_empty() {
+        return Err(Report::new(ApiClientError::EmptyUpdate));
+    }
+
+    Ok(mask)
+}
+
+fn collect_paths(
+    fields: &Map<String, Value>,
+    prefix: &str,
+    atomic_fields: &[&str],
+    paths: &mut Vec<String>,
+) {
+    for (name, value) in fields {
+        let path = format!("{prefix}{name}");
+        match value {
+            Value::Null => {}
+            Value::Object(nested)
+                if !nested.is_empty() && !atomic_fields.contains(&path.as_str()) =>
+            {
+                collect_paths(nested, &format!("{path}."), atomic_fields, paths)
+            }
+            _ => paths.push(path),
+        }
+    }
+}
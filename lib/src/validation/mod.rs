@@ -0,0 +1,231 @@
+//! Client-side validation of user attributes, mirroring the checks done by the Auth API
+
+#[cfg(test)]
+mod test;
+
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Maximum length of a user ID
+pub const MAX_UID_LENGTH: usize = 128;
+/// Minimum length of a user password
+pub const MIN_PASSWORD_LENGTH: usize = 6;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("User ID must be 1 to {MAX_UID_LENGTH} characters long")]
+    InvalidUid,
+    #[error("Email address is malformed")]
+    InvalidEmail,
+    #[error("Phone number is not in E.164 format")]
+    InvalidPhoneNumber,
+    #[error("Photo URL is not an absolute URL")]
+    InvalidPhotoUrl,
+    #[error("Password must be at least {MIN_PASSWORD_LENGTH} characters long")]
+    InvalidPassword,
+    #[error("Display name is empty")]
+    InvalidDisplayName,
+    #[error("Tenant ID must only contain letters, digits and hyphens")]
+    InvalidTenantId,
+}
+
+impl ValidationError {
+    /// Name of the rejected user attribute as used by the Auth API
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::InvalidUid => "localId",
+            Self::InvalidEmail => "email",
+            Self::InvalidPhoneNumber => "phoneNumber",
+            Self::InvalidPhotoUrl => "photoUrl",
+            Self::InvalidPassword => "password",
+            Self::InvalidDisplayName => "displayName",
+            Self::InvalidTenantId => "tenantId",
+        }
+    }
+}
+
+pub(crate) fn is_uid(uid: &str) -> bool {
+    !uid.is_empty() && uid.chars().count() <= MAX_UID_LENGTH
+}
+
+pub(crate) fn is_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn is_e164(phone_number: &str) -> bool {
+    match phone_number.strip_prefix('+') {
+        Some(digits) => {
+            (2..=15).contains(&digits.len())
+                && !digits.starts_with('0')
+                && digits.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Absolute URL with scheme and host
+pub(crate) fn is_url(url: &str) -> bool {
+    url.parse::<http::Uri>()
+        .is_ok_and(|uri| uri.scheme().is_some() && uri.host().is_some_and(|host| !host.is_empty()))
+}
+
+pub(crate) fn is_password(password: &str) -> bool {
+    password.chars().count() >= MIN_PASSWORD_LENGTH
+}
+
+pub(crate) fn is_tenant_id(tenant_id: &str) -> bool {
+    !tenant_id.is_empty()
+        && tenant_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+macro_rules! validated_string {
+    ($(#[$doc:meta])* $name:ident, $check:path, $error:expr) => {
+        $(#[$doc])*
+        #[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(value: impl Into<String>) -> Result<Self, ValidationError> {
+                let value = value.into();
+                match $check(&value) {
+                    true => Ok(Self(value)),
+                    false => Err($error),
+                }
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = ValidationError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = ValidationError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ValidationError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::new(value)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+validated_string!(
+    /// User ID of 1 to 128 characters
+    Uid,
+    is_uid,
+    ValidationError::InvalidUid
+);
+validated_string!(
+    /// Email address with a local part and a domain
+    Email,
+    is_email,
+    ValidationError::InvalidEmail
+);
+validated_string!(
+    /// Phone number in E.164 format, e.g. `+11234567890`
+    PhoneNumber,
+    is_e164,
+    ValidationError::InvalidPhoneNumber
+);
+validated_string!(
+    /// Absolute URL of a profile photo
+    PhotoUrl,
+    is_url,
+    ValidationError::InvalidPhotoUrl
+);
+validated_string!(
+    /// Identity Platform tenant ID, e.g. `customer-a-1x2y`
+    TenantId,
+    is_tenant_id,
+    ValidationError::InvalidTenantId
+);
+
+/// Password of at least 6 characters, never printed by `Debug`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Password(String);
+
+impl Password {
+    pub fn new(value: impl Into<String>) -> Result<Self, ValidationError> {
+        let value = value.into();
+        match is_password(&value) {
+            true => Ok(Self(value)),
+            false => Err(ValidationError::InvalidPassword),
+        }
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl TryFrom<String> for Password {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<&str> for Password {
+    type Error = ValidationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
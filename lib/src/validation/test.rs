@@ -0,0 +1,61 @@
+use super::{Email, Password, PhoneNumber, PhotoUrl, TenantId, Uid, ValidationError};
+
+#[test]
+fn test_validated_values() {
+    assert!(Uid::new("user1").is_ok());
+    assert_eq!(Uid::new(""), Err(ValidationError::InvalidUid));
+    assert_eq!(Uid::new("u".repeat(129)), Err(ValidationError::InvalidUid));
+
+    assert_eq!(
+        "me@example.com".parse::<Email>().unwrap().as_str(),
+        "me@example.com"
+    );
+    assert_eq!(Email::new("me@"), Err(ValidationError::InvalidEmail));
+    assert_eq!(
+        Email::new("me @example.com"),
+        Err(ValidationError::InvalidEmail)
+    );
+
+    assert!(PhoneNumber::new("+11234567890").is_ok());
+    assert_eq!(
+        PhoneNumber::new("11234567890"),
+        Err(ValidationError::InvalidPhoneNumber)
+    );
+    assert_eq!(
+        PhoneNumber::new("+0123"),
+        Err(ValidationError::InvalidPhoneNumber)
+    );
+    assert_eq!(
+        PhoneNumber::new("+1234567890123456"),
+        Err(ValidationError::InvalidPhoneNumber)
+    );
+
+    assert!(TenantId::new("customer-a-1x2y").is_ok());
+    for tenant_id in ["", "..", "a/b", "a?b"] {
+        assert_eq!(
+            TenantId::new(tenant_id),
+            Err(ValidationError::InvalidTenantId)
+        );
+    }
+
+    assert!(PhotoUrl::new("https://example.com/photo.png").is_ok());
+    assert_eq!(
+        PhotoUrl::new("/photo.png"),
+        Err(ValidationError::InvalidPhotoUrl)
+    );
+    assert_eq!(
+        PhotoUrl::new("example.com"),
+        Err(ValidationError::InvalidPhotoUrl)
+    );
+
+    assert_eq!(
+        Password::new("12345"),
+        Err(ValidationError::InvalidPassword)
+    );
+    assert_eq!(
+        format!("{:?}", Password::new("123456").unwrap()),
+        "Password(***)"
+    );
+
+    assert_eq!(ValidationError::InvalidPhoneNumber.field(), "phoneNumber");
+}